@@ -0,0 +1,85 @@
+//! what compressing frame payloads buys on the line, and what it costs the cpu
+
+use std::hint::black_box;
+
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use morse::{Compress, Decompress, FrameEncoder, LinkConfig, MessageType};
+
+const MESSAGES: &[(&str, &str)] = &[
+    ("tx", "UCSC CSE 121 ABCDEFGHIJKLM NOPQRSTUVWXYZ 12345 67890"),
+    ("short", "Hello ESP32"),
+    (
+        "status",
+        "the reading on the north sensor is at 1200, check that it is not stuck",
+    ),
+    (
+        "prose",
+        "It was the best of times, it was the worst of times, it was the age of \
+         wisdom, it was the age of foolishness, it was the epoch of belief",
+    ),
+];
+
+fn line_bits(msg: &str, compress: bool) -> usize {
+    let link = LinkConfig {
+        compress,
+        ..LinkConfig::FRAMES
+    };
+    link.transmission(msg, 0).unwrap().count()
+}
+
+fn bit_rate(c: &mut Criterion) {
+    let link = LinkConfig::FRAMES;
+    println!(
+        "chars per second on the line at {}us a bit:",
+        link.time_step_micros
+    );
+    for (name, msg) in MESSAGES {
+        let rate =
+            |bits: usize| msg.len() as f64 * 1e6 / (bits as u64 * link.time_step_micros) as f64;
+        let (plain, compressed) = (line_bits(msg, false), line_bits(msg, true));
+        println!(
+            "  {name:<8} {:8.0} plain  {:8.0} compressed  {:+.1}%",
+            rate(plain),
+            rate(compressed),
+            100.0 * (plain as f64 / compressed as f64 - 1.0)
+        );
+    }
+
+    let mut group = c.benchmark_group("frame");
+    for (name, msg) in MESSAGES {
+        group.throughput(Throughput::Bytes(msg.len() as u64));
+        group.bench_function(format!("{name}/plain"), |b| {
+            b.iter(|| {
+                FrameEncoder::new(MessageType::Text, 0, black_box(msg.as_bytes()))
+                    .unwrap()
+                    .count()
+            })
+        });
+        group.bench_function(format!("{name}/compressed"), |b| {
+            b.iter(|| {
+                FrameEncoder::compressed(MessageType::Text, 0, black_box(msg.as_bytes()))
+                    .unwrap()
+                    .count()
+            })
+        });
+    }
+    group.finish();
+}
+
+fn round_trip(c: &mut Criterion) {
+    let mut group = c.benchmark_group("dictionary");
+    for (name, msg) in MESSAGES {
+        let compressed: Vec<u8> = Compress::new(msg.as_bytes()).collect();
+        group.throughput(Throughput::Bytes(msg.len() as u64));
+        group.bench_function(format!("{name}/compress"), |b| {
+            b.iter(|| Compress::new(black_box(msg.as_bytes())).count())
+        });
+        group.bench_function(format!("{name}/decompress"), |b| {
+            b.iter(|| Decompress::new(black_box(&compressed)).unwrap().count())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bit_rate, round_trip);
+criterion_main!(benches);
@@ -0,0 +1,327 @@
+//! what rx spends on every bit, against the typestate parser it used to run.
+//! at 83kHz a sample has to be dealt with in 12us, all of it included. rx
+//! built with `soft` feeds the decoder soft readings, so that path is
+//! measured too
+
+use std::collections::VecDeque;
+use std::hint::black_box;
+use std::time::Instant;
+
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use morse::{
+    Bit, BitSequece, DecoderEvent, DecoderEvents, LineCode, LineDecoder, MorseBit,
+    MorseBitSequence, MorseConversion, MorseDecoder, MorseEncoder, MorseSymbol, START_SEQUENCE,
+    Soft, TAIL_BITS, UnaryCode, UnaryDecoder,
+};
+
+const MESSAGE: &str = "UCSC CSE 121 ABCDEFGHIJKLM NOPQRSTUVWXYZ 12345 67890";
+
+/// rx's parser from before the decoder moved into this crate, kept as it was
+/// apart from the error type: it clones the element into a conversion on
+/// every Lo, and both sequences again on the way out
+mod legacy {
+    use super::*;
+
+    pub struct WaitingForStart;
+    pub struct ListeningForMessage;
+    pub struct Processing;
+
+    pub struct Parser<State> {
+        state: std::marker::PhantomData<State>,
+        start_queue: Option<VecDeque<Bit>>,
+        bit_seq: BitSequece,
+        morse_seq: MorseBitSequence,
+    }
+
+    pub fn element(value: BitSequece) -> Result<MorseBit, ()> {
+        use Bit::*;
+        use MorseBit::*;
+
+        if value.starts_with(&[Lo]) && value.len() == 1 {
+            return Ok(Dot);
+        }
+        if value.starts_with(&[Hi, Lo]) && value.len() == 2 {
+            return Ok(CharBreak);
+        }
+        if value.starts_with(&[Hi, Hi, Lo]) && value.len() == 3 {
+            return Ok(Dash);
+        }
+        if value.starts_with(&[Hi, Hi, Hi, Lo]) && value.len() == 4 {
+            return Ok(LineBreak);
+        }
+        if value.starts_with(&[Hi, Hi, Hi, Hi, Lo]) && value.len() == 5 {
+            return Ok(WordBreak);
+        }
+        Err(())
+    }
+
+    impl Parser<WaitingForStart> {
+        pub fn new() -> Self {
+            Self {
+                state: std::marker::PhantomData,
+                start_queue: Some(VecDeque::with_capacity(12)),
+                bit_seq: BitSequece::new(),
+                morse_seq: MorseBitSequence::new(),
+            }
+        }
+
+        pub fn process(&mut self, bit: Bit) -> Option<Parser<ListeningForMessage>> {
+            let start_queue = self.start_queue.as_mut().unwrap();
+            if start_queue.len() == START_SEQUENCE.len() {
+                start_queue.pop_front();
+                start_queue.push_back(bit);
+                if start_queue.make_contiguous() == START_SEQUENCE {
+                    return Some(Parser {
+                        state: std::marker::PhantomData,
+                        start_queue: None,
+                        bit_seq: BitSequece::new(),
+                        morse_seq: MorseBitSequence::new(),
+                    });
+                }
+            } else {
+                start_queue.push_back(bit);
+            }
+            None
+        }
+    }
+
+    impl Parser<ListeningForMessage> {
+        pub fn listening() -> Self {
+            Parser {
+                state: std::marker::PhantomData,
+                start_queue: None,
+                bit_seq: BitSequece::new(),
+                morse_seq: MorseBitSequence::new(),
+            }
+        }
+
+        pub fn process(&mut self, bit: Bit) -> Option<Result<Parser<Processing>, ()>> {
+            if self.bit_seq.push(bit).is_err() {
+                return Some(Err(()));
+            }
+
+            if bit == Bit::Lo {
+                match element(self.bit_seq.clone()) {
+                    Ok(m_bit) => {
+                        self.bit_seq.clear();
+                        if self.morse_seq.push(m_bit).is_err() {
+                            return Some(Err(()));
+                        }
+                        if m_bit == MorseBit::LineBreak {
+                            return Some(Ok(Parser {
+                                state: std::marker::PhantomData,
+                                start_queue: None,
+                                bit_seq: self.bit_seq.clone(),
+                                morse_seq: self.morse_seq.clone(),
+                            }));
+                        }
+                    }
+                    Err(e) => return Some(Err(e)),
+                }
+            }
+            None
+        }
+    }
+
+    impl Parser<Processing> {
+        pub fn len(&self) -> usize {
+            self.morse_seq.len()
+        }
+
+        pub fn message(&mut self) -> String {
+            let mut msg = String::new();
+            for slice in self
+                .morse_seq
+                .split(|e| *e == MorseBit::CharBreak || *e == MorseBit::LineBreak)
+            {
+                if !slice.is_empty() {
+                    msg.push(char::from_morse_slice(slice).unwrap());
+                }
+            }
+            msg.to_lowercase()
+        }
+    }
+
+    pub fn receive(bits: &[Bit]) -> String {
+        let mut start = Parser::new();
+        let mut listener = None;
+        for bit in bits {
+            match &mut listener {
+                None => listener = start.process(*bit),
+                Some(listening) => {
+                    if let Some(parser) = listening.process(*bit) {
+                        return parser.unwrap().message();
+                    }
+                }
+            }
+        }
+        panic!("the message never ended")
+    }
+}
+
+fn receive(bits: &[Bit]) -> String {
+    let mut decoder = MorseDecoder::new();
+    let mut msg = String::new();
+    for bit in bits {
+        if text(&decoder.push(*bit).unwrap(), &mut msg) {
+            return msg.to_lowercase();
+        }
+    }
+    panic!("the message never ended")
+}
+
+// how rx built with `soft` runs it, every reading through the trellis
+fn receive_soft(readings: &[Soft]) -> String {
+    let mut decoder = MorseDecoder::new();
+    let mut msg = String::new();
+    for soft in readings {
+        if text(&decoder.push_soft(*soft).unwrap(), &mut msg) {
+            return msg.to_lowercase();
+        }
+    }
+    panic!("the message never ended")
+}
+
+// adds the chars in `events` to `msg`, true once the frame is over
+fn text(events: &DecoderEvents, msg: &mut String) -> bool {
+    for event in events {
+        match event {
+            DecoderEvent::Symbol(MorseSymbol::Char(c)) => msg.push(*c),
+            DecoderEvent::FrameEnd => return true,
+            _ => {}
+        }
+    }
+    false
+}
+
+// the line idles Lo before the start sequence, which the old parser relied on
+const IDLE: usize = 4;
+
+fn line() -> Vec<Bit> {
+    [Bit::Lo; IDLE]
+        .into_iter()
+        .chain(START_SEQUENCE)
+        .chain(MorseEncoder::from(MESSAGE).map(Result::unwrap))
+        .collect()
+}
+
+// adc readings minus the threshold, some of them close to it but none on
+// the wrong side. the trellis decides an element behind, so they go on into
+// the tail tx sends after every frame
+fn readings(bits: &[Bit]) -> Vec<Soft> {
+    bits.iter()
+        .chain(&[Bit::Lo; TAIL_BITS])
+        .enumerate()
+        .map(|(i, bit)| {
+            let level = 20 + (i * 37 % 300) as i16;
+            match bit {
+                Bit::Hi => Soft(level),
+                Bit::Lo => Soft(-level),
+            }
+        })
+        .collect()
+}
+
+// the old parser from the start sequence on, up to the state it hands over
+fn legacy_listen(payload: &[Bit]) -> usize {
+    let mut parser = legacy::Parser::listening();
+    for bit in payload {
+        if let Some(done) = parser.process(*bit) {
+            return done.unwrap().len();
+        }
+    }
+    panic!("the message never ended")
+}
+
+// the same stretch through the unary decoder
+fn listen(decoder: &mut UnaryDecoder, payload: &[Bit]) -> usize {
+    decoder.reset();
+    payload
+        .iter()
+        .filter_map(|bit| decoder.push(*bit).unwrap())
+        .count()
+}
+
+const ROUNDS: u32 = 100;
+
+fn batch<T>(bits: &[T], f: &mut impl FnMut(&[T]) -> usize) -> f64 {
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        black_box(f(black_box(bits)));
+    }
+    start.elapsed().as_nanos() as f64 / (ROUNDS as f64 * bits.len() as f64)
+}
+
+// ns per bit for `old` and `new`, the best of batches taken in turns, so
+// whatever else the machine is up to doesn't land on one side only
+fn per_bit<T, U>(
+    (old_bits, mut old): (&[T], impl FnMut(&[T]) -> usize),
+    (new_bits, mut new): (&[U], impl FnMut(&[U]) -> usize),
+) -> (f64, f64) {
+    const BATCHES: u32 = 50;
+    (0..BATCHES).fold((f64::INFINITY, f64::INFINITY), |(o, n), _| {
+        (
+            o.min(batch(old_bits, &mut old)),
+            n.min(batch(new_bits, &mut new)),
+        )
+    })
+}
+
+fn speedup(c: &mut Criterion) {
+    let bits = line();
+    let payload = &bits[IDLE + START_SEQUENCE.len()..];
+    let mut decoder = UnaryCode::COMPACT.decoder();
+    assert_eq!(legacy::receive(&bits), receive(&bits));
+    assert_eq!(legacy_listen(payload), listen(&mut decoder, payload));
+
+    println!("ns per bit of the tx message:");
+    let (old, new) = per_bit(
+        (payload, legacy_listen),
+        (payload, |payload: &[Bit]| listen(&mut decoder, payload)),
+    );
+    println!(
+        "  elements {old:6.1} legacy {new:6.1} now  {:.1}x",
+        old / new
+    );
+    let legacy = |bits: &[Bit]| legacy::receive(bits).len();
+    let (old, new) = per_bit((&bits, legacy), (&bits, |bits: &[Bit]| receive(bits).len()));
+    println!(
+        "  text     {old:6.1} legacy {new:6.1} now  {:.1}x",
+        old / new
+    );
+    assert!(old > new, "the decoder is slower than the old parser");
+    let soft = readings(&bits);
+    assert_eq!(receive_soft(&soft), receive(&bits));
+    let (old, new) = per_bit(
+        (&bits, legacy),
+        (&soft, |soft: &[Soft]| receive_soft(soft).len()),
+    );
+    println!(
+        "  soft     {old:6.1} legacy {new:6.1} now  {:.1}x",
+        old / new
+    );
+
+    let mut group = c.benchmark_group("elements");
+    group.throughput(Throughput::Elements(payload.len() as u64));
+    group.bench_function("legacy", |b| b.iter(|| legacy_listen(black_box(payload))));
+    group.bench_function("unary", |b| {
+        b.iter(|| listen(&mut decoder, black_box(payload)))
+    });
+    group.finish();
+
+    // the decoder walks the tree and keeps error context as it goes, where the
+    // old parser left all of it for the end. most bits only count towards an
+    // element, so the text comes out ahead, but by less than the elements do:
+    // every char break costs a lookup and an event. soft readings add the
+    // trellis, which weighs all eight runs an element could be at on every
+    // one of them, and make it about five times slower than the old parser
+    let mut group = c.benchmark_group("text");
+    group.throughput(Throughput::Elements(bits.len() as u64));
+    group.bench_function("legacy", |b| b.iter(|| legacy::receive(black_box(&bits))));
+    group.bench_function("decoder", |b| b.iter(|| receive(black_box(&bits))));
+    group.bench_function("soft", |b| b.iter(|| receive_soft(black_box(&soft))));
+    group.finish();
+}
+
+criterion_group!(benches, speedup);
+criterion_main!(benches);
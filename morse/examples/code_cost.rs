@@ -0,0 +1,67 @@
+//! average line bits per char of a corpus, keyed with the ITU table and with
+//! code tables fitted to letter frequencies
+//!
+//! `cargo run --example code_cost -- corpus.txt`, or pipe the corpus in
+
+use std::collections::HashMap;
+use std::io::Read;
+
+use morse::{
+    Alphabet, AnyLineCode, CodeTable, ENGLISH, ItuCode, MAX_CODE_SYMBOLS, MorseEncoder, UnaryCode,
+    fold,
+};
+
+fn main() -> std::io::Result<()> {
+    let mut corpus = String::new();
+    match std::env::args().nth(1) {
+        Some(path) => corpus = std::fs::read_to_string(path)?,
+        None => _ = std::io::stdin().read_to_string(&mut corpus)?,
+    }
+
+    // only what latin can send, so every table keys the same text
+    let text: String = corpus
+        .chars()
+        .map(fold)
+        .filter(|c| Alphabet::Latin.char_sequence(*c).is_ok())
+        .collect();
+    let skipped = corpus.chars().count() - text.chars().count();
+    let chars = text.chars().filter(|c| !matches!(c, ' ' | '\n')).count();
+    if chars == 0 {
+        eprintln!("nothing to send in the corpus");
+        return Ok(());
+    }
+
+    let mut counts = HashMap::new();
+    for c in text.chars().filter(|c| !matches!(c, ' ' | '\n')) {
+        *counts.entry(c).or_insert(0u32) += 1;
+    }
+    let mut profile: Vec<_> = counts.into_iter().collect();
+    profile.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    profile.truncate(MAX_CODE_SYMBOLS);
+    let fitted: &'static CodeTable =
+        Box::leak(Box::new(CodeTable::for_unary(&profile, UnaryCode::COMPACT)));
+
+    let per_char = |alphabet: Alphabet, code: AnyLineCode| {
+        let bits = MorseEncoder::with_line_code(text.chars(), code)
+            .with_alphabet(alphabet)
+            .collect::<Result<Vec<_>, _>>()
+            .expect("the text was checked")
+            .len();
+        bits as f64 / chars as f64
+    };
+
+    let compact = AnyLineCode::Unary(UnaryCode::COMPACT);
+    let itu = per_char(Alphabet::Latin, compact);
+    println!("{chars} chars, {skipped} skipped, bits per char on the compact code:");
+    println!("  itu morse        {itu:6.2}");
+    for (name, table) in [("english table", &ENGLISH), ("fitted to corpus", fitted)] {
+        let bits = per_char(Alphabet::Custom(table), compact);
+        let saved = 100.0 * (1.0 - bits / itu);
+        println!("  {name:<16} {bits:6.2}  {saved:+.1}% saved");
+    }
+    println!(
+        "  itu timing       {:6.2}  for reference",
+        per_char(Alphabet::Latin, AnyLineCode::Itu(ItuCode::new(20)))
+    );
+    Ok(())
+}
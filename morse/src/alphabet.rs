@@ -0,0 +1,358 @@
+use crate::{
+    CodeTable, DECODE_TREE, ErrorKind, INVERSE_MORSE_TABLE, MorseBit, MorseError, MorseSymbol,
+    MorseTree,
+};
+
+/// the character sets a link can key. latin is the international table
+/// everything else falls back on, so digits, punctuation and prosigns are the
+/// same in all of them, and a pattern an alphabet doesn't use still decodes
+/// as its latin letter.
+///
+/// most alphabets reuse the latin patterns for their own letters, so which one
+/// is meant has to be agreed on up front. wabun is the exception: it's shifted
+/// into mid message with [`crate::Prosign::DO`] and back out with [`crate::Prosign::SN`],
+/// which the encoder and decoder do on their own.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Alphabet {
+    #[default]
+    Latin,
+    /// russian
+    Cyrillic,
+    Greek,
+    Hebrew,
+    Arabic,
+    /// japanese katakana. hiragana is sent as katakana, and voiced kana as
+    /// their plain kana followed by a separate `゛` or `゜`
+    Wabun,
+    /// patterns fitted to how often each char is sent, see [`CodeTable`].
+    /// tables are statics, so this one can't be serialized
+    #[cfg_attr(feature = "serde", serde(skip))]
+    Custom(&'static CodeTable),
+}
+
+pub static CYRILLIC_TREE: MorseTree = MorseTree::new(CYRILLIC).with_fallback(INVERSE_MORSE_TABLE);
+pub static GREEK_TREE: MorseTree = MorseTree::new(GREEK).with_fallback(INVERSE_MORSE_TABLE);
+pub static HEBREW_TREE: MorseTree = MorseTree::new(HEBREW).with_fallback(INVERSE_MORSE_TABLE);
+pub static ARABIC_TREE: MorseTree = MorseTree::new(ARABIC).with_fallback(INVERSE_MORSE_TABLE);
+pub static WABUN_TREE: MorseTree = MorseTree::new(WABUN).with_fallback(INVERSE_MORSE_TABLE);
+
+impl Alphabet {
+    pub const ALL: [Alphabet; 6] = [
+        Alphabet::Latin,
+        Alphabet::Cyrillic,
+        Alphabet::Greek,
+        Alphabet::Hebrew,
+        Alphabet::Arabic,
+        Alphabet::Wabun,
+    ];
+
+    /// the letters this alphabet has on top of the latin table
+    pub const fn letters(self) -> &'static [(&'static [MorseBit], MorseSymbol)] {
+        match self {
+            Alphabet::Latin => &[],
+            Alphabet::Cyrillic => CYRILLIC,
+            Alphabet::Greek => GREEK,
+            Alphabet::Hebrew => HEBREW,
+            Alphabet::Arabic => ARABIC,
+            Alphabet::Wabun => WABUN,
+            Alphabet::Custom(_) => &[],
+        }
+    }
+
+    pub const fn tree(self) -> &'static MorseTree {
+        match self {
+            Alphabet::Latin => &DECODE_TREE,
+            Alphabet::Cyrillic => &CYRILLIC_TREE,
+            Alphabet::Greek => &GREEK_TREE,
+            Alphabet::Hebrew => &HEBREW_TREE,
+            Alphabet::Arabic => &ARABIC_TREE,
+            Alphabet::Wabun => &WABUN_TREE,
+            Alphabet::Custom(table) => table.tree(),
+        }
+    }
+
+    /// where [`crate::Prosign::SN`] leaves a wabun passage
+    pub const fn shift_out(self) -> Alphabet {
+        match self {
+            Alphabet::Wabun => Alphabet::Latin,
+            alphabet => alphabet,
+        }
+    }
+
+    /// looks up the pattern for `c` after [`fold`]ing it. only chars that
+    /// come back as themselves are supported, so a latin letter whose
+    /// pattern this alphabet uses for one of its own is not
+    pub fn char_sequence(self, c: char) -> Result<&'static [MorseBit], MorseError> {
+        let folded = fold(c);
+        let pattern = match self.pattern(folded) {
+            Some(pattern) => pattern,
+            None => crate::char_sequence(folded)?,
+        };
+        match self.tree().decode(pattern) {
+            Ok(MorseSymbol::Char(decoded)) if decoded == folded => Ok(pattern),
+            _ => Err(ErrorKind::UnsupportedChar(c).into()),
+        }
+    }
+
+    pub fn decode(self, sequence: &[MorseBit]) -> Result<MorseSymbol, MorseError> {
+        self.tree().decode(sequence)
+    }
+
+    // the pattern this alphabet gives `c` on top of the latin table
+    fn pattern(self, c: char) -> Option<&'static [MorseBit]> {
+        if let Alphabet::Custom(table) = self {
+            return table.pattern(c);
+        }
+        self.letters()
+            .iter()
+            .find(|(_, symbol)| *symbol == MorseSymbol::Char(c))
+            .map(|(pattern, _)| *pattern)
+    }
+}
+
+/// the form `c` is sent and decoded as: uppercase, without accents, final
+/// letter forms or small kana, and hiragana as katakana. voiced kana still
+/// need splitting with [`voicing_mark`]
+pub fn fold(c: char) -> char {
+    if c.is_ascii() {
+        return c.to_ascii_uppercase();
+    }
+
+    let mut upper = c.to_uppercase();
+    let c = match (upper.next(), upper.next()) {
+        (Some(upper), None) => upper,
+        _ => c,
+    };
+
+    match c {
+        'Ё' => 'Е',
+        'Ά' => 'Α',
+        'Έ' => 'Ε',
+        'Ή' => 'Η',
+        'Ί' | 'Ϊ' => 'Ι',
+        'Ό' => 'Ο',
+        'Ύ' | 'Ϋ' => 'Υ',
+        'Ώ' => 'Ω',
+        'ך' => 'כ',
+        'ם' => 'מ',
+        'ן' => 'נ',
+        'ף' => 'פ',
+        'ץ' => 'צ',
+        'أ' | 'إ' | 'آ' | 'ٱ' => 'ا',
+        // the combining marks have spacing twins that fit in a char slot
+        '\u{3099}' => '゛',
+        '\u{309a}' => '゜',
+        // hiragana sits 0x60 below its katakana
+        'ぁ'..='ゖ' => fold(char::from_u32(c as u32 + 0x60).unwrap_or(c)),
+        'ァ' | 'ィ' | 'ゥ' | 'ェ' | 'ォ' | 'ッ' | 'ャ' | 'ュ' | 'ョ' | 'ヮ' => {
+            char::from_u32(c as u32 + 1).unwrap_or(c)
+        }
+        _ => c,
+    }
+}
+
+/// splits a voiced or half voiced katakana into its plain kana and the mark
+/// that's sent after it, `ガ` into `カ` and `゛`
+pub fn voicing_mark(c: char) -> Option<(char, char)> {
+    let code = c as u32;
+    let (plain, mark) = match c {
+        // ガ to ヂ take the odd slots after their plain kana
+        'ガ'..='ヂ' if code.is_multiple_of(2) => (code - 1, '゛'),
+        'ヅ' | 'デ' | 'ド' => (code - 1, '゛'),
+        // バ and パ follow each of ハ ヒ フ ヘ ホ
+        'バ'..='ポ' => match (code - 'ハ' as u32) % 3 {
+            1 => (code - 1, '゛'),
+            2 => (code - 2, '゜'),
+            _ => return None,
+        },
+        'ヴ' => ('ウ' as u32, '゛'),
+        _ => return None,
+    };
+    Some((char::from_u32(plain)?, mark))
+}
+
+use MorseBit::*;
+
+// russian letters, ё is sent as е
+const CYRILLIC: &[(&[MorseBit], MorseSymbol)] = &[
+    (&[Dot, Dash], MorseSymbol::Char('А')),
+    (&[Dash, Dot, Dot, Dot], MorseSymbol::Char('Б')),
+    (&[Dot, Dash, Dash], MorseSymbol::Char('В')),
+    (&[Dash, Dash, Dot], MorseSymbol::Char('Г')),
+    (&[Dash, Dot, Dot], MorseSymbol::Char('Д')),
+    (&[Dot], MorseSymbol::Char('Е')),
+    (&[Dot, Dot, Dot, Dash], MorseSymbol::Char('Ж')),
+    (&[Dash, Dash, Dot, Dot], MorseSymbol::Char('З')),
+    (&[Dot, Dot], MorseSymbol::Char('И')),
+    (&[Dot, Dash, Dash, Dash], MorseSymbol::Char('Й')),
+    (&[Dash, Dot, Dash], MorseSymbol::Char('К')),
+    (&[Dot, Dash, Dot, Dot], MorseSymbol::Char('Л')),
+    (&[Dash, Dash], MorseSymbol::Char('М')),
+    (&[Dash, Dot], MorseSymbol::Char('Н')),
+    (&[Dash, Dash, Dash], MorseSymbol::Char('О')),
+    (&[Dot, Dash, Dash, Dot], MorseSymbol::Char('П')),
+    (&[Dot, Dash, Dot], MorseSymbol::Char('Р')),
+    (&[Dot, Dot, Dot], MorseSymbol::Char('С')),
+    (&[Dash], MorseSymbol::Char('Т')),
+    (&[Dot, Dot, Dash], MorseSymbol::Char('У')),
+    (&[Dot, Dot, Dash, Dot], MorseSymbol::Char('Ф')),
+    (&[Dot, Dot, Dot, Dot], MorseSymbol::Char('Х')),
+    (&[Dash, Dot, Dash, Dot], MorseSymbol::Char('Ц')),
+    (&[Dash, Dash, Dash, Dot], MorseSymbol::Char('Ч')),
+    (&[Dash, Dash, Dash, Dash], MorseSymbol::Char('Ш')),
+    (&[Dash, Dash, Dot, Dash], MorseSymbol::Char('Щ')),
+    (&[Dash, Dash, Dot, Dash, Dash], MorseSymbol::Char('Ъ')),
+    (&[Dash, Dot, Dash, Dash], MorseSymbol::Char('Ы')),
+    (&[Dash, Dot, Dot, Dash], MorseSymbol::Char('Ь')),
+    (&[Dot, Dot, Dash, Dot, Dot], MorseSymbol::Char('Э')),
+    (&[Dot, Dot, Dash, Dash], MorseSymbol::Char('Ю')),
+    (&[Dot, Dash, Dot, Dash], MorseSymbol::Char('Я')),
+];
+
+// greek letters, accents are dropped
+const GREEK: &[(&[MorseBit], MorseSymbol)] = &[
+    (&[Dot, Dash], MorseSymbol::Char('Α')),
+    (&[Dash, Dot, Dot, Dot], MorseSymbol::Char('Β')),
+    (&[Dash, Dash, Dot], MorseSymbol::Char('Γ')),
+    (&[Dash, Dot, Dot], MorseSymbol::Char('Δ')),
+    (&[Dot], MorseSymbol::Char('Ε')),
+    (&[Dash, Dash, Dot, Dot], MorseSymbol::Char('Ζ')),
+    (&[Dot, Dot, Dot, Dot], MorseSymbol::Char('Η')),
+    (&[Dash, Dot, Dash, Dot], MorseSymbol::Char('Θ')),
+    (&[Dot, Dot], MorseSymbol::Char('Ι')),
+    (&[Dash, Dot, Dash], MorseSymbol::Char('Κ')),
+    (&[Dot, Dash, Dot, Dot], MorseSymbol::Char('Λ')),
+    (&[Dash, Dash], MorseSymbol::Char('Μ')),
+    (&[Dash, Dot], MorseSymbol::Char('Ν')),
+    (&[Dash, Dot, Dot, Dash], MorseSymbol::Char('Ξ')),
+    (&[Dash, Dash, Dash], MorseSymbol::Char('Ο')),
+    (&[Dot, Dash, Dash, Dot], MorseSymbol::Char('Π')),
+    (&[Dot, Dash, Dot], MorseSymbol::Char('Ρ')),
+    (&[Dot, Dot, Dot], MorseSymbol::Char('Σ')),
+    (&[Dash], MorseSymbol::Char('Τ')),
+    (&[Dash, Dot, Dash, Dash], MorseSymbol::Char('Υ')),
+    (&[Dot, Dot, Dash, Dot], MorseSymbol::Char('Φ')),
+    (&[Dash, Dash, Dash, Dash], MorseSymbol::Char('Χ')),
+    (&[Dash, Dash, Dot, Dash], MorseSymbol::Char('Ψ')),
+    (&[Dot, Dash, Dash], MorseSymbol::Char('Ω')),
+];
+
+// hebrew letters, final forms are sent as the regular ones
+const HEBREW: &[(&[MorseBit], MorseSymbol)] = &[
+    (&[Dot, Dash], MorseSymbol::Char('א')),
+    (&[Dash, Dot, Dot, Dot], MorseSymbol::Char('ב')),
+    (&[Dash, Dash, Dot], MorseSymbol::Char('ג')),
+    (&[Dash, Dot, Dot], MorseSymbol::Char('ד')),
+    (&[Dash, Dash, Dash], MorseSymbol::Char('ה')),
+    (&[Dot], MorseSymbol::Char('ו')),
+    (&[Dash, Dash, Dot, Dot], MorseSymbol::Char('ז')),
+    (&[Dot, Dot, Dot, Dot], MorseSymbol::Char('ח')),
+    (&[Dot, Dot, Dash], MorseSymbol::Char('ט')),
+    (&[Dot, Dot], MorseSymbol::Char('י')),
+    (&[Dash, Dot, Dash], MorseSymbol::Char('כ')),
+    (&[Dot, Dash, Dot, Dot], MorseSymbol::Char('ל')),
+    (&[Dash, Dash], MorseSymbol::Char('מ')),
+    (&[Dash, Dot], MorseSymbol::Char('נ')),
+    (&[Dash, Dot, Dash, Dot], MorseSymbol::Char('ס')),
+    (&[Dot, Dash, Dash, Dash], MorseSymbol::Char('ע')),
+    (&[Dot, Dash, Dash, Dot], MorseSymbol::Char('פ')),
+    (&[Dot, Dash, Dash], MorseSymbol::Char('צ')),
+    (&[Dash, Dash, Dot, Dash], MorseSymbol::Char('ק')),
+    (&[Dot, Dash, Dot], MorseSymbol::Char('ר')),
+    (&[Dot, Dot, Dot], MorseSymbol::Char('ש')),
+    (&[Dash], MorseSymbol::Char('ת')),
+];
+
+// arabic letters, hamza carrying alefs are sent as a plain alef
+const ARABIC: &[(&[MorseBit], MorseSymbol)] = &[
+    (&[Dot, Dash], MorseSymbol::Char('ا')),
+    (&[Dash, Dot, Dot, Dot], MorseSymbol::Char('ب')),
+    (&[Dash], MorseSymbol::Char('ت')),
+    (&[Dash, Dot, Dash, Dot], MorseSymbol::Char('ث')),
+    (&[Dot, Dash, Dash, Dash], MorseSymbol::Char('ج')),
+    (&[Dot, Dot, Dot, Dot], MorseSymbol::Char('ح')),
+    (&[Dash, Dash, Dash], MorseSymbol::Char('خ')),
+    (&[Dash, Dot, Dot], MorseSymbol::Char('د')),
+    (&[Dash, Dash, Dot, Dot], MorseSymbol::Char('ذ')),
+    (&[Dot, Dash, Dot], MorseSymbol::Char('ر')),
+    (&[Dash, Dash, Dash, Dot], MorseSymbol::Char('ز')),
+    (&[Dot, Dot, Dot], MorseSymbol::Char('س')),
+    (&[Dash, Dash, Dash, Dash], MorseSymbol::Char('ش')),
+    (&[Dash, Dot, Dot, Dash], MorseSymbol::Char('ص')),
+    (&[Dot, Dot, Dot, Dash], MorseSymbol::Char('ض')),
+    (&[Dot, Dot, Dash], MorseSymbol::Char('ط')),
+    (&[Dash, Dot, Dash, Dash], MorseSymbol::Char('ظ')),
+    (&[Dot, Dash, Dot, Dash], MorseSymbol::Char('ع')),
+    (&[Dash, Dash, Dot], MorseSymbol::Char('غ')),
+    (&[Dot, Dot, Dash, Dot], MorseSymbol::Char('ف')),
+    (&[Dash, Dash, Dot, Dash], MorseSymbol::Char('ق')),
+    (&[Dash, Dot, Dash], MorseSymbol::Char('ك')),
+    (&[Dot, Dash, Dot, Dot], MorseSymbol::Char('ل')),
+    (&[Dash, Dash], MorseSymbol::Char('م')),
+    (&[Dash, Dot], MorseSymbol::Char('ن')),
+    (&[Dot, Dot, Dash, Dot, Dot], MorseSymbol::Char('ه')),
+    (&[Dot, Dash, Dash], MorseSymbol::Char('و')),
+    (&[Dot, Dot], MorseSymbol::Char('ي')),
+    (&[Dot], MorseSymbol::Char('ء')),
+];
+
+// katakana in iroha order, then the voicing marks and punctuation
+const WABUN: &[(&[MorseBit], MorseSymbol)] = &[
+    (&[Dot, Dash], MorseSymbol::Char('イ')),
+    (&[Dot, Dash, Dot, Dash], MorseSymbol::Char('ロ')),
+    (&[Dash, Dot, Dot, Dot], MorseSymbol::Char('ハ')),
+    (&[Dash, Dot, Dash, Dot], MorseSymbol::Char('ニ')),
+    (&[Dash, Dot, Dot], MorseSymbol::Char('ホ')),
+    (&[Dot], MorseSymbol::Char('ヘ')),
+    (&[Dot, Dot, Dash, Dot, Dot], MorseSymbol::Char('ト')),
+    (&[Dot, Dot, Dash, Dot], MorseSymbol::Char('チ')),
+    (&[Dash, Dash, Dot], MorseSymbol::Char('リ')),
+    (&[Dot, Dot, Dot, Dot], MorseSymbol::Char('ヌ')),
+    (&[Dash, Dot, Dash, Dash, Dot], MorseSymbol::Char('ル')),
+    (&[Dot, Dash, Dash, Dash], MorseSymbol::Char('ヲ')),
+    (&[Dash, Dot, Dash], MorseSymbol::Char('ワ')),
+    (&[Dot, Dash, Dot, Dot], MorseSymbol::Char('カ')),
+    (&[Dash, Dash], MorseSymbol::Char('ヨ')),
+    (&[Dash, Dot], MorseSymbol::Char('タ')),
+    (&[Dash, Dash, Dash], MorseSymbol::Char('レ')),
+    (&[Dash, Dash, Dash, Dot], MorseSymbol::Char('ソ')),
+    (&[Dot, Dash, Dash, Dot], MorseSymbol::Char('ツ')),
+    (&[Dash, Dash, Dot, Dash], MorseSymbol::Char('ネ')),
+    (&[Dot, Dash, Dot], MorseSymbol::Char('ナ')),
+    (&[Dot, Dot, Dot], MorseSymbol::Char('ラ')),
+    (&[Dash], MorseSymbol::Char('ム')),
+    (&[Dot, Dot, Dash], MorseSymbol::Char('ウ')),
+    (&[Dot, Dash, Dot, Dot, Dash], MorseSymbol::Char('ヰ')),
+    (&[Dot, Dot, Dash, Dash], MorseSymbol::Char('ノ')),
+    (&[Dot, Dash, Dot, Dot, Dot], MorseSymbol::Char('オ')),
+    (&[Dot, Dot, Dot, Dash], MorseSymbol::Char('ク')),
+    (&[Dot, Dash, Dash], MorseSymbol::Char('ヤ')),
+    (&[Dash, Dot, Dot, Dash], MorseSymbol::Char('マ')),
+    (&[Dash, Dot, Dash, Dash], MorseSymbol::Char('ケ')),
+    (&[Dash, Dash, Dot, Dot], MorseSymbol::Char('フ')),
+    (&[Dash, Dash, Dash, Dash], MorseSymbol::Char('コ')),
+    (&[Dash, Dot, Dash, Dash, Dash], MorseSymbol::Char('エ')),
+    (&[Dot, Dash, Dot, Dash, Dash], MorseSymbol::Char('テ')),
+    (&[Dash, Dash, Dot, Dash, Dash], MorseSymbol::Char('ア')),
+    (&[Dash, Dot, Dash, Dot, Dash], MorseSymbol::Char('サ')),
+    (&[Dash, Dot, Dash, Dot, Dot], MorseSymbol::Char('キ')),
+    (&[Dash, Dot, Dot, Dash, Dash], MorseSymbol::Char('ユ')),
+    (&[Dash, Dot, Dot, Dot, Dash], MorseSymbol::Char('メ')),
+    (&[Dot, Dot, Dash, Dot, Dash], MorseSymbol::Char('ミ')),
+    (&[Dash, Dash, Dot, Dash, Dot], MorseSymbol::Char('シ')),
+    (&[Dot, Dash, Dash, Dot, Dot], MorseSymbol::Char('ヱ')),
+    (&[Dash, Dash, Dot, Dot, Dash], MorseSymbol::Char('ヒ')),
+    (&[Dash, Dot, Dot, Dash, Dot], MorseSymbol::Char('モ')),
+    (&[Dot, Dash, Dash, Dash, Dot], MorseSymbol::Char('セ')),
+    (&[Dash, Dash, Dash, Dot, Dash], MorseSymbol::Char('ス')),
+    (&[Dot, Dash, Dot, Dash, Dot], MorseSymbol::Char('ン')),
+    (&[Dot, Dot], MorseSymbol::Char('゛')),
+    (&[Dot, Dot, Dash, Dash, Dot], MorseSymbol::Char('゜')),
+    (&[Dot, Dash, Dash, Dot, Dash], MorseSymbol::Char('ー')),
+    (&[Dot, Dash, Dot, Dash, Dot, Dash], MorseSymbol::Char('、')),
+    (&[Dot, Dash, Dot, Dash, Dot, Dot], MorseSymbol::Char('」')),
+    (&[Dash, Dot, Dash, Dash, Dot, Dash], MorseSymbol::Char('（')),
+    (&[Dot, Dash, Dot, Dot, Dash, Dot], MorseSymbol::Char('）')),
+];
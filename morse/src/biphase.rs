@@ -0,0 +1,215 @@
+use core::iter::FusedIterator;
+
+use crate::{Bit, ErrorKind, MorseError};
+
+/// self clocking line codes that put a transition in the middle of every bit,
+/// so the receiver can follow the transmitter's clock instead of trusting its
+/// own sample rate. each bit goes out as two half bits.
+///
+/// this sits underneath everything else, start sequence included: the encoder
+/// wraps the finished bit stream and the decoder hands recovered bits to the
+/// frame decoder.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Biphase {
+    /// IEEE 802.3 polarity: Hi is sent as `HL`, Lo as `LH`
+    Manchester,
+    /// the mid-bit transition is always there, and a Lo also flips the line at
+    /// the start of the bit. only transitions matter, so it survives an
+    /// inverted receiver
+    DifferentialManchester,
+}
+
+impl Biphase {
+    /// wraps a bit stream, the line is assumed to idle Lo before it
+    pub fn encode<I: IntoIterator<Item = Bit>>(self, bits: I) -> BiphaseEncoder<I::IntoIter> {
+        BiphaseEncoder {
+            mode: self,
+            bits: bits.into_iter(),
+            level: Bit::Lo,
+            second_half: None,
+        }
+    }
+
+    pub const fn decoder(self, samples_per_half_bit: u32) -> BiphaseDecoder {
+        BiphaseDecoder::new(self, samples_per_half_bit)
+    }
+}
+
+const fn flip(bit: Bit) -> Bit {
+    match bit {
+        Bit::Hi => Bit::Lo,
+        Bit::Lo => Bit::Hi,
+    }
+}
+
+pub struct BiphaseEncoder<I> {
+    mode: Biphase,
+    bits: I,
+    // line level at the end of the last half bit sent
+    level: Bit,
+    second_half: Option<Bit>,
+}
+
+impl<I: Iterator<Item = Bit>> Iterator for BiphaseEncoder<I> {
+    type Item = Bit;
+
+    fn next(&mut self) -> Option<Bit> {
+        let half = match self.second_half.take() {
+            Some(half) => half,
+            None => {
+                let bit = self.bits.next()?;
+                let first = match self.mode {
+                    Biphase::Manchester => bit,
+                    Biphase::DifferentialManchester if bit == Bit::Lo => flip(self.level),
+                    Biphase::DifferentialManchester => self.level,
+                };
+                self.second_half = Some(flip(first));
+                first
+            }
+        };
+
+        self.level = half;
+        Some(half)
+    }
+}
+
+impl<I: FusedIterator<Item = Bit>> FusedIterator for BiphaseEncoder<I> {}
+
+// the half bit estimate is kept in 1/16ths of a sample so it can creep after
+// a slow drift without rounding away every correction
+const FRAC_BITS: u32 = 4;
+
+/// recovers bits from an oversampled biphase line by timing the runs between
+/// edges. every run is one or two half bits long, and the estimate of a half
+/// bit is nudged towards each measured run, so the decoder keeps lock while
+/// the transmitter's clock wanders.
+///
+/// a run longer than two half bits is idle line. the first edge after idle is
+/// placed by assuming every transmission starts with a Hi bit, which the start
+/// sequence does.
+#[derive(Clone, Debug)]
+pub struct BiphaseDecoder {
+    mode: Biphase,
+    nominal: u32,
+    half: u32,
+    level: Bit,
+    run: u32,
+    synced: bool,
+    // true when the next half bit is the second one of its bit
+    mid_bit: bool,
+    first_half: Bit,
+    // level of the second half of the previous bit, for differential decoding
+    last_half: Bit,
+}
+
+impl BiphaseDecoder {
+    pub const fn new(mode: Biphase, samples_per_half_bit: u32) -> Self {
+        let nominal = if samples_per_half_bit == 0 {
+            1
+        } else {
+            samples_per_half_bit
+        } << FRAC_BITS;
+
+        Self {
+            mode,
+            nominal,
+            half: nominal,
+            level: Bit::Lo,
+            run: 0,
+            synced: false,
+            mid_bit: false,
+            first_half: Bit::Lo,
+            last_half: Bit::Lo,
+        }
+    }
+
+    /// current estimate of a half bit, in samples
+    pub const fn samples_per_half_bit(&self) -> u32 {
+        (self.half + (1 << (FRAC_BITS - 1))) >> FRAC_BITS
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new(self.mode, self.nominal >> FRAC_BITS);
+    }
+
+    /// feeds one thresholded sample, returning a bit whenever a mid-bit edge
+    /// goes by. that edge is where the bit is decided, so nothing waits on
+    /// the line changing again afterwards
+    pub fn push(&mut self, sample: Bit) -> Result<Option<Bit>, MorseError> {
+        if sample == self.level {
+            // capped so the fixed point math below can never overflow
+            self.run = (self.run + 1).min(u32::MAX >> (FRAC_BITS + 2));
+            // long enough without an edge to be idle, resync on the next one
+            if self.synced && (self.run << FRAC_BITS) > 3 * self.half {
+                self.synced = false;
+            }
+            return Ok(None);
+        }
+
+        let (level, run) = (self.level, self.run);
+        self.level = sample;
+        self.run = 1;
+
+        if !self.synced {
+            return Ok(self.sync(level));
+        }
+
+        let halves = match run << FRAC_BITS {
+            r if 2 * r < 3 * self.half => 1,
+            r if 2 * r < 5 * self.half => 2,
+            _ => {
+                self.synced = false;
+                return Err(ErrorKind::UnknownBitSequence.into());
+            }
+        };
+
+        // track the clock, but never let it wander off by more than half of
+        // what we were told, or one glitch could drag it somewhere useless
+        let measured = (run << FRAC_BITS) / halves;
+        let half = self.half as i64 + (measured as i64 - self.half as i64) / 8;
+        self.half = (half as u32).clamp(self.nominal / 2, self.nominal * 3 / 2);
+
+        for _ in 0..halves {
+            if self.mid_bit {
+                if level == self.first_half {
+                    self.synced = false;
+                    return Err(ErrorKind::UnknownBitSequence.into());
+                }
+                self.last_half = level;
+            } else {
+                self.first_half = level;
+            }
+            self.mid_bit = !self.mid_bit;
+        }
+
+        Ok(self.mid_bit.then(|| self.decide()))
+    }
+
+    // the edge that ends an idle stretch. with the line idling Lo, manchester
+    // starts its Hi bit right on that edge, otherwise the edge is the middle
+    // of the first bit and the idle level was its first half
+    fn sync(&mut self, idle: Bit) -> Option<Bit> {
+        self.synced = true;
+
+        if self.mode == Biphase::Manchester && idle == Bit::Lo {
+            self.mid_bit = false;
+            return None;
+        }
+
+        self.first_half = idle;
+        self.last_half = idle;
+        self.mid_bit = true;
+        Some(Bit::Hi)
+    }
+
+    fn decide(&self) -> Bit {
+        match self.mode {
+            Biphase::Manchester => self.first_half,
+            // no transition at the start of the bit means Hi
+            Biphase::DifferentialManchester if self.first_half == self.last_half => Bit::Hi,
+            Biphase::DifferentialManchester => Bit::Lo,
+        }
+    }
+}
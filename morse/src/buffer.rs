@@ -0,0 +1,48 @@
+use core::ops::DerefMut;
+
+use crate::{ErrorKind, MorseError};
+
+/// storage for whatever a decoder collects while it receives.
+///
+/// a `heapless::Vec<T, N>` holds `N` items and fails with
+/// [`ErrorKind::FullBuffer`] past that, so firmware decides up front what it
+/// can spare. with the `alloc` feature a plain `Vec<T>` grows as needed, for
+/// host tools that shouldn't be capped at all
+pub trait Buffer<T>: DerefMut<Target = [T]> {
+    /// an empty buffer
+    const NEW: Self;
+
+    fn push(&mut self, item: T) -> Result<(), MorseError>;
+
+    fn truncate(&mut self, len: usize);
+
+    fn clear(&mut self) {
+        self.truncate(0);
+    }
+}
+
+impl<T, const N: usize> Buffer<T> for heapless::Vec<T, N> {
+    const NEW: Self = heapless::Vec::new();
+
+    fn push(&mut self, item: T) -> Result<(), MorseError> {
+        heapless::Vec::push(self, item).map_err(|_| ErrorKind::FullBuffer.into())
+    }
+
+    fn truncate(&mut self, len: usize) {
+        heapless::Vec::truncate(self, len);
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T> Buffer<T> for alloc::vec::Vec<T> {
+    const NEW: Self = alloc::vec::Vec::new();
+
+    fn push(&mut self, item: T) -> Result<(), MorseError> {
+        alloc::vec::Vec::push(self, item);
+        Ok(())
+    }
+
+    fn truncate(&mut self, len: usize) {
+        alloc::vec::Vec::truncate(self, len);
+    }
+}
@@ -0,0 +1,369 @@
+use core::iter::FusedIterator;
+
+use crate::{
+    Bit, Buffer, Checksum, DecoderState, Digest, ErrorKind, Fec, FecEncoder, MorseError, Position,
+    Preamble, PreambleMatch, StartDetector,
+};
+
+// start bit, eight data bits and a stop bit
+const FRAME_BITS: u8 = 10;
+
+// sent right before the break with fec on, see `CLOSE_CORRECTED`
+const END_MARK: u8 = 0xff;
+
+// what goes out after the last byte: the break, a whole frame of Lo
+const CLOSE: &[u16] = &[0];
+
+// with fec a 0 that lost its stop bit looks just like a break, so the break
+// is doubled to tell them apart, and a mark that's far from 0 goes first so
+// the byte right before it never is one
+const CLOSE_CORRECTED: &[u16] = &[framed(END_MARK), 0, 0];
+
+const fn framed(byte: u8) -> u16 {
+    (byte as u16) << 1 | 1 << (FRAME_BITS - 1)
+}
+
+/// sends raw bytes instead of text, framed like a UART: a Lo start bit, the
+/// eight data bits least significant first, then a Hi stop bit. the start bit
+/// guarantees an edge every byte, so a [`crate::Sampler`] never drifts far.
+///
+/// the payload is closed with a break, a whole frame of Lo where the stop bit
+/// should have been Hi, which no real byte can produce.
+///
+/// with a [`Checksum`] set, it goes out big endian right after the payload,
+/// and with [`Fec`] both of them are protected before they're framed. a 0
+/// that lost its stop bit is a break too, so with fec on the break is sent
+/// twice, after a `0xff` the decoder drops.
+///
+/// like [`crate::MorseEncoder`], the start sequence is not included.
+pub struct ByteEncoder<I> {
+    bytes: FecEncoder<Trailed<I>>,
+    // bits of the current frame still to go out, lowest first
+    frame: u16,
+    left: u8,
+    // frames still to go out once the bytes run out
+    close: &'static [u16],
+}
+
+impl<I: Iterator<Item = u8>> ByteEncoder<I> {
+    pub fn new(bytes: impl IntoIterator<IntoIter = I>) -> Self {
+        let bytes = Trailed {
+            bytes: bytes.into_iter(),
+            digest: Checksum::None.digest(),
+            trailer: None,
+        };
+
+        Self {
+            bytes: Fec::None.encode(bytes),
+            frame: 0,
+            left: 0,
+            close: CLOSE,
+        }
+    }
+
+    pub fn with_checksum(mut self, checksum: Checksum) -> Self {
+        self.bytes.get_mut().digest = checksum.digest();
+        self
+    }
+
+    pub fn with_fec(mut self, fec: Fec) -> Self {
+        self.bytes = fec.encode(self.bytes.into_inner());
+        self.close = match fec {
+            Fec::None => CLOSE,
+            _ => CLOSE_CORRECTED,
+        };
+        self
+    }
+}
+
+// the payload followed by its checksum
+struct Trailed<I> {
+    bytes: I,
+    digest: Digest,
+    // trailer bytes still to send, `None` until the payload runs out
+    trailer: Option<usize>,
+}
+
+impl<I: Iterator<Item = u8>> Iterator for Trailed<I> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        if self.trailer.is_none()
+            && let Some(byte) = self.bytes.next()
+        {
+            self.digest.update(&[byte]);
+            return Some(byte);
+        }
+
+        let left = self.trailer.get_or_insert(self.digest.checksum().size());
+        *left = left.checked_sub(1)?;
+        Some((self.digest.value() >> (*left * 8)) as u8)
+    }
+}
+
+impl<'a> From<&'a [u8]> for ByteEncoder<core::iter::Copied<core::slice::Iter<'a, u8>>> {
+    fn from(value: &'a [u8]) -> Self {
+        ByteEncoder::new(value.iter().copied())
+    }
+}
+
+impl<I: Iterator<Item = u8>> Iterator for ByteEncoder<I> {
+    type Item = Bit;
+
+    fn next(&mut self) -> Option<Bit> {
+        if self.left == 0 {
+            self.frame = match self.bytes.next() {
+                Some(byte) => framed(byte),
+                None => {
+                    let (frame, rest) = self.close.split_first()?;
+                    self.close = rest;
+                    *frame
+                }
+            };
+            self.left = FRAME_BITS;
+        }
+
+        let bit = if self.frame & 1 == 1 {
+            Bit::Hi
+        } else {
+            Bit::Lo
+        };
+        self.frame >>= 1;
+        self.left -= 1;
+        Some(bit)
+    }
+}
+
+impl<I: Iterator<Item = u8>> FusedIterator for ByteEncoder<I> {}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ByteEvent {
+    FrameStart,
+    Byte(u8),
+    /// the break arrived, [`ByteDecoder::payload`] holds the whole frame
+    FrameEnd,
+}
+
+/// the receiving half of [`ByteEncoder`]. it hunts for the start sequence the
+/// same way [`crate::MorseDecoder`] does, then reports every byte as its stop
+/// bit arrives and collects them into `B`, see [`Buffer`].
+///
+/// the payload stays around after the frame ends, until the next one starts.
+/// any error drops the frame in progress and goes back to waiting for the
+/// start sequence. its position counts bytes instead of symbols.
+///
+/// with a [`Checksum`] set, bytes are reported once they're too far from the
+/// end to be the trailer, which shares the buffer with the payload and is
+/// dropped from it once verified.
+///
+/// with [`Fec`] set, nothing is known for sure until the whole frame is in and
+/// corrected, so there are no `Byte` events, and the buffer has to fit the encoded
+/// frame. a missing stop bit is left for the correction to sort out too, and
+/// the frame only ends on the doubled break.
+#[derive(Clone, Debug)]
+pub struct ByteDecoder<B: Buffer<u8>> {
+    state: DecoderState,
+    start: StartDetector,
+    checksum: Checksum,
+    fec: Fec,
+    frame: u16,
+    received: u8,
+    // breaks in a row so far, with fec on the first might have been a 0
+    breaks: u8,
+    // line bits since the preamble, for errors
+    bits: u64,
+    payload: B,
+}
+
+impl<B: Buffer<u8>> Default for ByteDecoder<B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<B: Buffer<u8>> ByteDecoder<B> {
+    pub const fn new() -> Self {
+        Self {
+            state: DecoderState::WaitingForStart,
+            start: StartDetector::new(Preamble::LEGACY),
+            checksum: Checksum::None,
+            fec: Fec::None,
+            frame: 0,
+            received: 0,
+            breaks: 0,
+            bits: 0,
+            payload: B::NEW,
+        }
+    }
+
+    pub const fn with_checksum(mut self, checksum: Checksum) -> Self {
+        self.checksum = checksum;
+        self
+    }
+
+    pub const fn with_fec(mut self, fec: Fec) -> Self {
+        self.fec = fec;
+        self
+    }
+
+    /// listens for `preamble` instead of the exact [`crate::START_SEQUENCE`]
+    pub fn with_preamble(mut self, preamble: Preamble) -> Self {
+        self.start = StartDetector::new(preamble);
+        self
+    }
+
+    /// where the current (or last) frame's preamble was found
+    pub fn preamble_match(&self) -> Option<PreambleMatch> {
+        self.start.last_match()
+    }
+
+    pub fn state(&self) -> DecoderState {
+        self.state
+    }
+
+    /// the bytes received so far, or the whole payload once the frame ended
+    pub fn payload(&self) -> &[u8] {
+        &self.payload
+    }
+
+    // for the frame layer to decrypt in place
+    #[cfg(feature = "aead")]
+    pub(crate) fn payload_mut(&mut self) -> &mut B {
+        &mut self.payload
+    }
+
+    pub fn reset(&mut self) {
+        self.state = DecoderState::WaitingForStart;
+        self.start.reset();
+        self.frame = 0;
+        self.received = 0;
+        self.breaks = 0;
+        self.bits = 0;
+        self.payload.clear();
+    }
+
+    pub fn push(&mut self, bit: Bit) -> Result<Option<ByteEvent>, MorseError> {
+        match self.state {
+            // keep the finished payload readable while we look for the next one
+            DecoderState::Processing => {
+                self.state = DecoderState::WaitingForStart;
+                Ok(self.wait_for_start(bit))
+            }
+            DecoderState::WaitingForStart => Ok(self.wait_for_start(bit)),
+            DecoderState::ListeningForMessage => {
+                self.start.skip(1);
+                self.listen(bit).map_err(|e| {
+                    let position = Position {
+                        bit: self.bits,
+                        symbol: self.payload.len(),
+                    };
+                    self.reset();
+                    e.at(position)
+                })
+            }
+        }
+    }
+
+    fn wait_for_start(&mut self, bit: Bit) -> Option<ByteEvent> {
+        self.start.push(bit)?;
+
+        self.frame = 0;
+        self.received = 0;
+        self.breaks = 0;
+        self.bits = 0;
+        self.payload.clear();
+        self.state = DecoderState::ListeningForMessage;
+        Some(ByteEvent::FrameStart)
+    }
+
+    fn listen(&mut self, bit: Bit) -> Result<Option<ByteEvent>, MorseError> {
+        self.bits += 1;
+        // the line may idle Hi between bytes, only a Lo starts the next one
+        if self.received == 0 && bit == Bit::Hi {
+            return Ok(None);
+        }
+
+        if bit == Bit::Hi {
+            self.frame |= 1 << self.received;
+        }
+        self.received += 1;
+        if self.received < FRAME_BITS {
+            return Ok(None);
+        }
+
+        let frame = core::mem::take(&mut self.frame);
+        self.received = 0;
+
+        let byte = (frame >> 1) as u8;
+        let stop = frame >> (FRAME_BITS - 1) == 1;
+        if self.fec != Fec::None {
+            return self.correctable(stop, byte);
+        }
+        match (stop, byte) {
+            (false, 0) => self.end(),
+            (true, byte) => {
+                self.payload.push(byte)?;
+                let trailer = self.checksum.size();
+                Ok(self
+                    .payload
+                    .len()
+                    .checked_sub(trailer + 1)
+                    .map(|i| ByteEvent::Byte(self.payload[i])))
+            }
+            // a missing stop bit with data in it is a framing error
+            (false, _) => Err(ErrorKind::UnknownBitSequence.into()),
+        }
+    }
+
+    // every byte is kept for the correction, stop bit or not. a lone break is
+    // only known to have been a 0 once the next byte shows up
+    fn correctable(&mut self, stop: bool, byte: u8) -> Result<Option<ByteEvent>, MorseError> {
+        if !stop && byte == 0 {
+            self.breaks += 1;
+            if self.breaks == 1 {
+                return Ok(None);
+            }
+            // the end mark carries nothing, even if it came through wrong
+            let Some(len) = self.payload.len().checked_sub(1) else {
+                return Err(ErrorKind::UnknownBitSequence.into());
+            };
+            self.payload.truncate(len);
+            return self.end();
+        }
+
+        for _ in 0..core::mem::take(&mut self.breaks) {
+            self.payload.push(0)?;
+        }
+        self.payload.push(byte)?;
+        Ok(None)
+    }
+
+    fn end(&mut self) -> Result<Option<ByteEvent>, MorseError> {
+        self.verify()?;
+        self.state = DecoderState::Processing;
+        Ok(Some(ByteEvent::FrameEnd))
+    }
+
+    fn verify(&mut self) -> Result<(), MorseError> {
+        let len = self.fec.decode(&mut self.payload)?;
+        self.payload.truncate(len);
+
+        let trailer = self.checksum.size();
+        let Some(end) = self.payload.len().checked_sub(trailer) else {
+            return Err(ErrorKind::UnknownBitSequence.into());
+        };
+
+        let expected = self.payload[end..]
+            .iter()
+            .fold(0, |crc, byte| crc << 8 | *byte as u16);
+        let actual = self.checksum.compute(&self.payload[..end]);
+        if expected != actual {
+            return Err(ErrorKind::ChecksumMismatch { expected, actual }.into());
+        }
+
+        self.payload.truncate(end);
+        Ok(())
+    }
+}
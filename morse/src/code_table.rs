@@ -0,0 +1,245 @@
+use crate::{INVERSE_MORSE_TABLE, MAX_DEPTH, MorseBit, MorseSymbol, MorseTree, UnaryCode};
+
+/// most symbols a [`CodeTable`] can be fitted to
+pub const MAX_CODE_SYMBOLS: usize = 64;
+
+// every pattern up to `MAX_DEPTH` elements, as heap indices like in the tree
+const NODES: usize = 1 << (MAX_DEPTH + 1);
+
+/// how often each letter turns up in english text, per mille
+pub const ENGLISH_FREQUENCIES: &[(char, u32)] = &[
+    ('E', 127),
+    ('T', 91),
+    ('A', 82),
+    ('O', 75),
+    ('I', 70),
+    ('N', 67),
+    ('S', 63),
+    ('H', 61),
+    ('R', 60),
+    ('D', 43),
+    ('L', 40),
+    ('C', 28),
+    ('U', 28),
+    ('M', 24),
+    ('W', 24),
+    ('F', 22),
+    ('G', 20),
+    ('Y', 20),
+    ('P', 19),
+    ('B', 15),
+    ('V', 10),
+    ('K', 8),
+    ('J', 2),
+    ('X', 2),
+    ('Q', 1),
+    ('Z', 1),
+];
+
+/// english letters fitted to the compact line code, use it with
+/// [`crate::Alphabet::Custom`]
+pub static ENGLISH: CodeTable = CodeTable::for_unary(ENGLISH_FREQUENCIES, UnaryCode::COMPACT);
+
+#[derive(Copy, Clone, PartialEq, Debug, Hash)]
+struct Entry {
+    c: char,
+    pattern: [MorseBit; MAX_DEPTH],
+    len: usize,
+}
+
+/// patterns handed out by how often their symbol is sent, so the common ones
+/// cost the fewest line bits. morse did the same for telegraph operators, but
+/// a dash costs three times a dot on the compact code and the ITU table
+/// wasn't made with that in mind.
+///
+/// chars are delimited by a char break anyway, so the code doesn't need to be
+/// prefix free like a huffman code, and giving the cheapest patterns to the
+/// most frequent symbols is as good as it gets. anything the profile leaves
+/// out falls back on the latin table, like with the other alphabets. only the
+/// latin letters' patterns are handed out again, digits, punctuation and
+/// prosigns keep theirs so numbers and the wabun shifts go out as usual.
+///
+/// ```
+/// use morse::{
+///     Alphabet, CHAR_SEQUENCE_LEN, CodeTable, MorseBit, MorseBitSequence, MorseConversion,
+///     UnaryCode,
+/// };
+///
+/// static CODE: CodeTable =
+///     CodeTable::for_unary(&[('E', 10), ('T', 8), ('Z', 3)], UnaryCode::COMPACT);
+///
+/// // a dash costs as much as three dots, the shorter one wins
+/// assert_eq!(CODE.pattern('Z'), Some(&[MorseBit::Dash][..]));
+/// let seq: MorseBitSequence<CHAR_SEQUENCE_LEN> =
+///     'z'.to_morse_bit_sequence_in(Alphabet::Custom(&CODE)).unwrap();
+/// assert_eq!(seq, CODE.pattern('Z').unwrap());
+/// ```
+#[derive(PartialEq, Debug, Hash)]
+pub struct CodeTable {
+    tree: MorseTree,
+    entries: [Entry; MAX_CODE_SYMBOLS],
+    len: usize,
+}
+
+// the patterns are only ever dots and dashes, which always equal themselves
+impl Eq for CodeTable {}
+
+// the whole tree is a lot to log, the chars say which table it is
+#[cfg(feature = "defmt")]
+impl defmt::Format for CodeTable {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "CodeTable(");
+        for (c, _) in self.symbols() {
+            defmt::write!(f, "{}", c);
+        }
+        defmt::write!(f, ")");
+    }
+}
+
+impl CodeTable {
+    /// fits a table to `profile`, pairs of a char and how often it's sent, for
+    /// a line code where a dot costs `dot` and a dash `dash`. chars should be
+    /// given the way [`crate::fold`] leaves them, uppercase. ties go to the
+    /// char listed first.
+    ///
+    /// panics with more than [`MAX_CODE_SYMBOLS`] chars, a char in there twice,
+    /// or a space or newline, which are sent as breaks
+    pub const fn new(profile: &[(char, u32)], dot: u32, dash: u32) -> Self {
+        assert!(
+            profile.len() <= MAX_CODE_SYMBOLS,
+            "too many symbols for a code table"
+        );
+
+        // cost of every pattern, each node adds its last element to its parent
+        let mut costs = [0; NODES];
+        let mut node = 2;
+        while node < NODES {
+            costs[node] = costs[node / 2] + if node % 2 == 0 { dot } else { dash };
+            node += 1;
+        }
+
+        let mut taken = reserved();
+        let mut placed = [false; MAX_CODE_SYMBOLS];
+        let mut tree = MorseTree::empty();
+        let mut entries = [Entry {
+            c: '\0',
+            pattern: [MorseBit::Dot; MAX_DEPTH],
+            len: 0,
+        }; MAX_CODE_SYMBOLS];
+
+        let mut len = 0;
+        while len < profile.len() {
+            // most frequent char that's left
+            let mut next = 0;
+            while placed[next] {
+                next += 1;
+            }
+            let mut i = next + 1;
+            while i < profile.len() {
+                if !placed[i] && profile[i].1 > profile[next].1 {
+                    next = i;
+                }
+                i += 1;
+            }
+            placed[next] = true;
+
+            // and the cheapest pattern that's left, shorter ones first on a tie
+            let mut best = 0;
+            let mut node = 2;
+            while node < NODES {
+                if !taken[node] && (best == 0 || costs[node] < costs[best]) {
+                    best = node;
+                }
+                node += 1;
+            }
+            taken[best] = true;
+
+            let c = profile[next].0;
+            assert!(c != ' ' && c != '\n', "breaks can't be in a code table");
+            let mut j = 0;
+            while j < len {
+                assert!(entries[j].c != c, "char listed twice in the profile");
+                j += 1;
+            }
+            entries[len] = Entry::new(c, best);
+            tree = tree.with_node(best, MorseSymbol::Char(c));
+            len += 1;
+        }
+
+        Self {
+            tree: tree.with_fallback(INVERSE_MORSE_TABLE),
+            entries,
+            len,
+        }
+    }
+
+    /// [`CodeTable::new`] with the costs of `code`
+    pub const fn for_unary(profile: &[(char, u32)], code: UnaryCode) -> Self {
+        Self::new(profile, code.cost(MorseBit::Dot), code.cost(MorseBit::Dash))
+    }
+
+    /// decodes the table's own patterns, and latin for everything else
+    pub const fn tree(&self) -> &MorseTree {
+        &self.tree
+    }
+
+    /// the pattern `c` was given, if it was in the profile
+    pub fn pattern(&self, c: char) -> Option<&[MorseBit]> {
+        self.symbols()
+            .find(|(symbol, _)| *symbol == c)
+            .map(|(_, pattern)| pattern)
+    }
+
+    /// every char in the table with its pattern, most frequent first
+    pub fn symbols(&self) -> impl Iterator<Item = (char, &[MorseBit])> {
+        self.entries[..self.len]
+            .iter()
+            .map(|entry| (entry.c, &entry.pattern[..entry.len]))
+    }
+}
+
+// the patterns of everything in the latin table but its letters
+const fn reserved() -> [bool; NODES] {
+    let mut taken = [false; NODES];
+    let mut i = 0;
+    while i < INVERSE_MORSE_TABLE.len() {
+        let (pattern, symbol) = INVERSE_MORSE_TABLE[i];
+        i += 1;
+        if let MorseSymbol::Char(c) = symbol
+            && c.is_ascii_alphabetic()
+        {
+            continue;
+        }
+
+        // the breaks aren't patterns of dots and dashes
+        let mut node = 1;
+        let mut j = 0;
+        while j < pattern.len() {
+            node = match pattern[j] {
+                MorseBit::Dot => node * 2,
+                MorseBit::Dash => node * 2 + 1,
+                _ => break,
+            };
+            j += 1;
+        }
+        if j == pattern.len() {
+            taken[node] = true;
+        }
+    }
+    taken
+}
+
+impl Entry {
+    const fn new(c: char, node: usize) -> Self {
+        let len = node.ilog2() as usize;
+        let mut pattern = [MorseBit::Dot; MAX_DEPTH];
+        let mut i = 0;
+        while i < len {
+            if node >> (len - 1 - i) & 1 == 1 {
+                pattern[i] = MorseBit::Dash;
+            }
+            i += 1;
+        }
+        Self { c, pattern, len }
+    }
+}
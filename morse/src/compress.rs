@@ -0,0 +1,154 @@
+use core::iter::FusedIterator;
+
+use crate::{ErrorKind, MorseError};
+
+/// common english words and letter groups, in both cases since messages come
+/// either way. a byte from `0x80` up stands for the entry at `byte - 0x80`.
+/// the list is part of the protocol, changing it means bumping
+/// [`crate::PROTOCOL_VERSION`]
+pub const DICTIONARY: &[&[u8]] = &[
+    b" the ", b" and ", b" that ", b" with ", b" this ", b" have ", b" from ", b" for ", b" you ",
+    b" are ", b" was ", b" not ", b" of ", b" to ", b" in ", b" is ", b" it ", b" on ", b" be ",
+    b" at ", b" a ", b"the ", b"tion", b"ing ", b"ing", b"ed ", b"er ", b"es ", b"s ", b"e ",
+    b"d ", b"t ", b"y ", b"th", b"he", b"in", b"er", b"an", b"re", b"on", b"at", b"en", b"nd",
+    b"es", b"or", b"te", b"st", b"ar", b"ou", b"it", b"is", b"al", b"le", b"ha", b"nt", b"ea",
+    b"se", b"ve", b"me", b"ll", b", ", b". ", b" THE ", b" AND ", b" THAT ", b" WITH ", b" THIS ",
+    b" HAVE ", b" FROM ", b" FOR ", b" YOU ", b" ARE ", b" WAS ", b" NOT ", b" OF ", b" TO ",
+    b" IN ", b" IS ", b" IT ", b" ON ", b" BE ", b" AT ", b" A ", b"THE ", b"TION", b"ING ",
+    b"ING", b"ED ", b"ER ", b"ES ", b"S ", b"E ", b"D ", b"T ", b"Y ", b"TH", b"HE", b"IN", b"ER",
+    b"AN", b"RE", b"ON", b"AT", b"EN", b"ND", b"ES", b"OR", b"TE", b"ST", b"AR", b"OU", b"IT",
+    b"IS", b"AL", b"LE", b"HA", b"NT", b"EA", b"SE", b"VE", b"ME", b"LL", b"00", b"  ",
+];
+
+// the next byte is sent as is, for bytes that would read as an entry
+const ESCAPE: u8 = 0xff;
+const FIRST_ENTRY: u8 = 0x80;
+
+const _: () = assert!(DICTIONARY.len() <= (ESCAPE - FIRST_ENTRY) as usize);
+
+/// squeezes a payload with [`DICTIONARY`], replacing the longest entry found
+/// at each point with its byte. ascii that matches nothing goes through
+/// unchanged, anything above it costs an extra byte, so binary data grows.
+///
+/// it works straight off the slice without buffering, so the compressed
+/// length can be found by just counting, see [`compressed_len`]
+#[derive(Clone, Debug)]
+pub struct Compress<'a> {
+    bytes: &'a [u8],
+    // the byte owed after an escape
+    escaped: Option<u8>,
+}
+
+impl<'a> Compress<'a> {
+    pub const fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes,
+            escaped: None,
+        }
+    }
+}
+
+impl Iterator for Compress<'_> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        if let Some(byte) = self.escaped.take() {
+            return Some(byte);
+        }
+
+        let longest = DICTIONARY
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| self.bytes.starts_with(entry))
+            .max_by_key(|(_, entry)| entry.len());
+        if let Some((i, entry)) = longest {
+            self.bytes = &self.bytes[entry.len()..];
+            return Some(FIRST_ENTRY + i as u8);
+        }
+
+        let (&byte, rest) = self.bytes.split_first()?;
+        self.bytes = rest;
+        if byte < FIRST_ENTRY {
+            return Some(byte);
+        }
+        self.escaped = Some(byte);
+        Some(ESCAPE)
+    }
+}
+
+impl FusedIterator for Compress<'_> {}
+
+/// how long `bytes` is once compressed
+pub fn compressed_len(bytes: &[u8]) -> usize {
+    Compress::new(bytes).count()
+}
+
+/// the receiving half of [`Compress`], expanding a payload again as it's read
+#[derive(Clone, Debug)]
+pub struct Decompress<'a> {
+    bytes: &'a [u8],
+    // what's left of the entry being expanded
+    entry: &'static [u8],
+    compressed: bool,
+}
+
+impl<'a> Decompress<'a> {
+    /// checks the whole payload up front, so reading it never fails. a byte
+    /// that's no entry or an escape with nothing after it is a
+    /// [`ErrorKind::MalformedFrame`]
+    pub fn new(bytes: &'a [u8]) -> Result<Self, MorseError> {
+        let mut rest = bytes;
+        while let Some((&byte, tail)) = rest.split_first() {
+            rest = match byte {
+                ESCAPE => tail.get(1..).ok_or(ErrorKind::MalformedFrame)?,
+                FIRST_ENTRY.. if (byte - FIRST_ENTRY) as usize >= DICTIONARY.len() => {
+                    return Err(ErrorKind::MalformedFrame.into());
+                }
+                _ => tail,
+            };
+        }
+
+        Ok(Self {
+            bytes,
+            entry: &[],
+            compressed: true,
+        })
+    }
+
+    // a payload that was never compressed, read as is
+    pub(crate) const fn plain(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes,
+            entry: &[],
+            compressed: false,
+        }
+    }
+}
+
+impl Iterator for Decompress<'_> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        if let Some((&byte, rest)) = self.entry.split_first() {
+            self.entry = rest;
+            return Some(byte);
+        }
+
+        let (&byte, rest) = self.bytes.split_first()?;
+        self.bytes = rest;
+        if !self.compressed || byte < FIRST_ENTRY {
+            return Some(byte);
+        }
+        if byte == ESCAPE {
+            let (&byte, rest) = self.bytes.split_first()?;
+            self.bytes = rest;
+            return Some(byte);
+        }
+
+        let (&first, rest) = DICTIONARY[(byte - FIRST_ENTRY) as usize].split_first()?;
+        self.entry = rest;
+        Some(first)
+    }
+}
+
+impl FusedIterator for Decompress<'_> {}
@@ -0,0 +1,104 @@
+/// optional integrity check appended to every frame, so rx can tell a clean
+/// read from a char that got corrupted into another valid one.
+///
+/// morse frames carry it as uppercase hex digits after the message, byte
+/// frames as big endian bytes after the payload.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Checksum {
+    #[default]
+    None,
+    /// CRC-8/SMBUS, poly 0x07
+    Crc8,
+    /// CRC-16/CCITT-FALSE, poly 0x1021 starting from 0xffff
+    Crc16,
+}
+
+impl Checksum {
+    /// bytes of trailer this adds to a frame
+    pub const fn size(self) -> usize {
+        match self {
+            Checksum::None => 0,
+            Checksum::Crc8 => 1,
+            Checksum::Crc16 => 2,
+        }
+    }
+
+    /// hex digits of trailer this adds to a morse frame
+    pub const fn digits(self) -> usize {
+        self.size() * 2
+    }
+
+    pub const fn digest(self) -> Digest {
+        let value = match self {
+            Checksum::Crc16 => 0xffff,
+            Checksum::None | Checksum::Crc8 => 0,
+        };
+        Digest {
+            checksum: self,
+            value,
+        }
+    }
+
+    pub fn compute(self, data: &[u8]) -> u16 {
+        let mut digest = self.digest();
+        digest.update(data);
+        digest.value()
+    }
+}
+
+/// a checksum being worked out a piece at a time, for the streaming encoders
+/// and decoders
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Digest {
+    checksum: Checksum,
+    value: u16,
+}
+
+impl Digest {
+    pub fn update(&mut self, data: &[u8]) {
+        for byte in data {
+            match self.checksum {
+                Checksum::None => {}
+                Checksum::Crc8 => {
+                    let mut crc = self.value as u8 ^ byte;
+                    for _ in 0..8 {
+                        crc = if crc & 0x80 != 0 {
+                            crc << 1 ^ 0x07
+                        } else {
+                            crc << 1
+                        };
+                    }
+                    self.value = crc as u16;
+                }
+                Checksum::Crc16 => {
+                    let mut crc = self.value ^ (*byte as u16) << 8;
+                    for _ in 0..8 {
+                        crc = if crc & 0x8000 != 0 {
+                            crc << 1 ^ 0x1021
+                        } else {
+                            crc << 1
+                        };
+                    }
+                    self.value = crc;
+                }
+            }
+        }
+    }
+
+    /// the text is checked as its utf-8 bytes
+    pub fn update_char(&mut self, c: char) {
+        self.update(c.encode_utf8(&mut [0; 4]).as_bytes());
+    }
+
+    pub const fn checksum(&self) -> Checksum {
+        self.checksum
+    }
+
+    pub const fn value(&self) -> u16 {
+        self.value
+    }
+}
@@ -0,0 +1,156 @@
+use core::fmt;
+
+use chacha20poly1305::{AeadInPlace, ChaCha20Poly1305, KeyInit, Nonce, Tag};
+use heapless::Deque;
+
+use crate::{ErrorKind, FrameHeader, MorseError};
+
+pub const KEY_LEN: usize = 32;
+
+/// bytes the poly1305 tag adds to the end of every encrypted payload
+pub const TAG_LEN: usize = 16;
+
+/// bytes of the session number every encrypted payload starts with, sent in
+/// the clear
+pub const SESSION_LEN: usize = 8;
+
+/// how many sessions rx remembers having moved on from, frames from any of
+/// them are refused as replays
+pub const RETIRED_SESSIONS: usize = 8;
+
+/// a key shared by tx and rx ahead of time, for frames sealed with
+/// chacha20-poly1305. the payload is encrypted and, together with the header,
+/// authenticated, so a frame from anyone without the key is refused.
+///
+/// the nonce is a session number followed by the frame's sequence number, so
+/// a key must never see the same pair twice. tx draws a random session every
+/// time it starts counting from 0, on boot and when the sequence wraps, and
+/// sends it in front of the payload. rx refuses a frame whose sequence
+/// doesn't move its session forward, see [`crate::FrameDecoder`].
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct FrameKey([u8; KEY_LEN]);
+
+impl FrameKey {
+    pub const fn new(key: [u8; KEY_LEN]) -> Self {
+        Self(key)
+    }
+
+    /// reads a key written as 64 hex digits, for keys handed in at build time
+    /// with `env!`. panics on anything else
+    pub const fn from_hex(hex: &str) -> Self {
+        const fn digit(c: u8) -> u8 {
+            match c {
+                b'0'..=b'9' => c - b'0',
+                b'a'..=b'f' => c - b'a' + 10,
+                b'A'..=b'F' => c - b'A' + 10,
+                _ => panic!("keys are written in hex"),
+            }
+        }
+
+        let hex = hex.as_bytes();
+        assert!(hex.len() == 2 * KEY_LEN, "keys are 64 hex digits");
+        let mut key = [0; KEY_LEN];
+        let mut i = 0;
+        while i < KEY_LEN {
+            key[i] = digit(hex[2 * i]) << 4 | digit(hex[2 * i + 1]);
+            i += 1;
+        }
+        Self(key)
+    }
+
+    /// encrypts `payload` in place, returning the tag to send after it.
+    /// `header` has to be the one it goes out with, length and flags included,
+    /// and `session` the one sent in front of the payload
+    pub fn seal(&self, session: u64, header: &FrameHeader, payload: &mut [u8]) -> [u8; TAG_LEN] {
+        self.cipher()
+            .encrypt_in_place_detached(
+                &nonce(session, header.sequence),
+                &header.to_bytes(),
+                payload,
+            )
+            .expect("frames are far below the cipher's limit")
+            .into()
+    }
+
+    /// decrypts `payload` in place if `tag` proves it came from someone with
+    /// the key, and leaves it alone otherwise. a session or header changed on
+    /// the way fails like a changed payload
+    pub fn open(
+        &self,
+        session: u64,
+        header: &FrameHeader,
+        payload: &mut [u8],
+        tag: &[u8; TAG_LEN],
+    ) -> Result<(), MorseError> {
+        self.cipher()
+            .decrypt_in_place_detached(
+                &nonce(session, header.sequence),
+                &header.to_bytes(),
+                payload,
+                Tag::from_slice(tag),
+            )
+            .map_err(|_| ErrorKind::AuthenticationFailed.into())
+    }
+
+    fn cipher(&self) -> ChaCha20Poly1305 {
+        ChaCha20Poly1305::new(&self.0.into())
+    }
+}
+
+// keep the key out of logs
+impl fmt::Debug for FrameKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("FrameKey(..)")
+    }
+}
+
+fn nonce(session: u64, sequence: u16) -> Nonce {
+    let mut nonce = Nonce::default();
+    nonce[..SESSION_LEN].copy_from_slice(&session.to_be_bytes());
+    nonce[10..].copy_from_slice(&sequence.to_be_bytes());
+    nonce
+}
+
+/// what rx remembers of the sealed frames it let through, so none of them
+/// gets through twice. within a session the sequence has to go up, and a
+/// session rx moved on from stays closed. only the last
+/// [`RETIRED_SESSIONS`] are kept, and nothing survives a restart of rx, so
+/// frames recorded from a session older than that can still be played back
+#[derive(Clone, Debug)]
+pub(crate) struct Replay {
+    last: Option<(u64, u16)>,
+    retired: Deque<u64, RETIRED_SESSIONS>,
+}
+
+impl Replay {
+    pub(crate) const fn new() -> Self {
+        Self {
+            last: None,
+            retired: Deque::new(),
+        }
+    }
+
+    /// fails with [`ErrorKind::ReplayedFrame`] if a frame numbered
+    /// `sequence` in `session` can't be new
+    pub(crate) fn check(&self, session: u64, sequence: u16) -> Result<(), MorseError> {
+        let replayed = match self.last {
+            Some((last, newest)) if last == session => sequence <= newest,
+            _ => self.retired.iter().any(|retired| *retired == session),
+        };
+        match replayed {
+            true => Err(ErrorKind::ReplayedFrame.into()),
+            false => Ok(()),
+        }
+    }
+
+    /// remembers a frame that passed [`Replay::check`] and its authentication
+    pub(crate) fn accept(&mut self, session: u64, sequence: u16) {
+        if let Some((last, _)) = self.last.filter(|(last, _)| *last != session) {
+            if self.retired.is_full() {
+                self.retired.pop_front();
+            }
+            let _ = self.retired.push_back(last);
+        }
+        self.last = Some((session, sequence));
+    }
+}
@@ -0,0 +1,331 @@
+use heapless::Deque;
+
+use crate::{
+    Alphabet, AnyLineCode, Bit, Checksum, DECODE_TREE, Digest, ErrorKind, LineCode, LineDecoder,
+    LinkConfig, MorseBit, MorseError, MorseSymbol, Position, Preamble, PreambleMatch, Prosign,
+    Soft, StartDetector, TreeCursor, UnaryCode,
+};
+
+// chars of the frame kept around to put in errors, a [`crate::PARTIAL_LEN`]
+// worth even if they all take four bytes
+const RECENT_CHARS: usize = crate::PARTIAL_LEN / 4;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DecoderState {
+    /// sliding over the line until the start sequence shows up
+    WaitingForStart,
+    /// inside a frame, turning bits into symbols
+    ListeningForMessage,
+    /// the frame just ended, the next bit starts the hunt for a new one
+    Processing,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DecoderEvent {
+    FrameStart,
+    Symbol(MorseSymbol),
+    FrameEnd,
+}
+
+/// a single bit can finish a character and the frame (or word) at once
+pub type DecoderEvents = heapless::Vec<DecoderEvent, 2>;
+
+/// streaming receiver: feed it one line bit at a time and it reports frame
+/// boundaries and every decoded symbol as soon as its char break arrives.
+///
+/// any error drops the frame in progress and goes back to waiting for the
+/// start sequence. the error says where in the frame it happened and carries
+/// the last few chars decoded before it.
+///
+/// with a [`Checksum`] set, the last few symbols are held back until the frame
+/// ends, since only then is it known they were the trailer. a frame whose
+/// trailer doesn't match fails with [`ErrorKind::ChecksumMismatch`] instead of
+/// ending.
+///
+/// every frame starts in the configured [`Alphabet`]. a [`Prosign::DO`] shifts
+/// into wabun and a [`Prosign::SN`] back out, neither is reported as a symbol.
+///
+/// [`MorseDecoder::push_soft`] takes undecided bits instead. the preamble is
+/// still looked for on hard decisions, but the frame after it goes through
+/// [`LineDecoder::push_soft`], so a line code that searches for the likeliest
+/// elements gets to.
+#[derive(Debug)]
+pub struct MorseDecoder<C: LineCode = UnaryCode> {
+    state: DecoderState,
+    start: StartDetector,
+    line: C::Decoder,
+    alphabet: Alphabet,
+    active: Alphabet,
+    cursor: TreeCursor<'static>,
+    checksum: Checksum,
+    digest: Digest,
+    // each with what its pattern spells in latin, which the trailer is keyed in
+    held: Deque<(MorseSymbol, Option<char>), { Checksum::Crc16.digits() }>,
+    // where the frame in progress is, for errors
+    bits: u64,
+    symbols: usize,
+    recent: Deque<char, RECENT_CHARS>,
+}
+
+impl Default for MorseDecoder {
+    fn default() -> Self {
+        MorseDecoder::new()
+    }
+}
+
+impl MorseDecoder {
+    pub fn new() -> Self {
+        Self::with_line_code(&UnaryCode::default())
+    }
+}
+
+impl MorseDecoder<AnyLineCode> {
+    /// listens for the preamble, line code and checksum from `config`
+    pub fn with_config(config: &LinkConfig) -> Self {
+        Self::with_line_code(&config.line_code)
+            .with_preamble(config.preamble)
+            .with_checksum(config.checksum)
+            .with_alphabet(config.alphabet)
+    }
+}
+
+impl<C: LineCode> MorseDecoder<C> {
+    pub fn with_line_code(code: &C) -> Self {
+        Self {
+            state: DecoderState::WaitingForStart,
+            start: StartDetector::new(Preamble::LEGACY),
+            line: code.decoder(),
+            alphabet: Alphabet::Latin,
+            active: Alphabet::Latin,
+            cursor: Alphabet::Latin.tree().cursor(),
+            checksum: Checksum::None,
+            digest: Checksum::None.digest(),
+            held: Deque::new(),
+            bits: 0,
+            symbols: 0,
+            recent: Deque::new(),
+        }
+    }
+
+    pub fn with_checksum(mut self, checksum: Checksum) -> Self {
+        self.checksum = checksum;
+        self.digest = checksum.digest();
+        self
+    }
+
+    pub fn with_alphabet(mut self, alphabet: Alphabet) -> Self {
+        self.alphabet = alphabet;
+        self.shift(alphabet);
+        self
+    }
+
+    /// listens for `preamble` instead of the exact [`crate::START_SEQUENCE`]
+    pub fn with_preamble(mut self, preamble: Preamble) -> Self {
+        self.start = StartDetector::new(preamble);
+        self
+    }
+
+    /// where the current (or last) frame's preamble was found
+    pub fn preamble_match(&self) -> Option<PreambleMatch> {
+        self.start.last_match()
+    }
+
+    pub fn state(&self) -> DecoderState {
+        self.state
+    }
+
+    /// the alphabet characters are being decoded in right now
+    pub fn active_alphabet(&self) -> Alphabet {
+        self.active
+    }
+
+    pub fn reset(&mut self) {
+        self.state = DecoderState::WaitingForStart;
+        // the frame's bits are only counted as it's dropped, not one by one
+        self.start.skip(self.bits);
+        self.start.reset();
+        self.line.reset();
+        self.shift(self.alphabet);
+        self.digest = self.checksum.digest();
+        self.held.clear();
+        self.bits = 0;
+        self.symbols = 0;
+        self.recent.clear();
+    }
+
+    #[inline]
+    pub fn push(&mut self, bit: Bit) -> Result<DecoderEvents, MorseError> {
+        self.step(bit, |line| line.push(bit))
+    }
+
+    /// like [`MorseDecoder::push`], for a bit that hasn't been decided yet.
+    /// elements may come out a few bits late, so leave the line idle for a
+    /// bit after each frame before starting the next
+    pub fn push_soft(&mut self, soft: Soft) -> Result<DecoderEvents, MorseError> {
+        self.step(soft.hard(), |line| line.push_soft(soft))
+    }
+
+    // runs for every bit, and most bits only count towards an element or
+    // move the cursor. that much stays inline, anything that can emit or fail
+    // is called out of line so it doesn't weigh on the rest
+    #[inline(always)]
+    fn step(
+        &mut self,
+        bit: Bit,
+        element: impl FnOnce(&mut C::Decoder) -> Result<Option<MorseBit>, MorseError>,
+    ) -> Result<DecoderEvents, MorseError> {
+        if self.state != DecoderState::ListeningForMessage {
+            let mut events = DecoderEvents::new();
+            self.wait_for_start(bit, &mut events);
+            return Ok(events);
+        }
+
+        self.bits += 1;
+        let m_bit = match element(&mut self.line) {
+            Ok(None) => return Ok(DecoderEvents::new()),
+            Ok(Some(m_bit)) => m_bit,
+            Err(e) => return Err(self.fail(e)),
+        };
+        let mut events = DecoderEvents::new();
+        let done = match m_bit {
+            MorseBit::Dot | MorseBit::Dash => self.cursor.push(m_bit),
+            _ => self.listen(m_bit, &mut events),
+        };
+        match done {
+            Ok(()) => Ok(events),
+            Err(e) => Err(self.fail(e)),
+        }
+    }
+
+    #[inline(never)]
+    fn wait_for_start(&mut self, bit: Bit, events: &mut DecoderEvents) {
+        if self.state == DecoderState::Processing {
+            self.reset();
+        }
+        if self.start.push(bit).is_some() {
+            self.line.reset();
+            self.state = DecoderState::ListeningForMessage;
+            emit(events, DecoderEvent::FrameStart);
+        }
+    }
+
+    // every break finishes the char in progress, and some add to it
+    #[inline(never)]
+    fn listen(&mut self, m_bit: MorseBit, events: &mut DecoderEvents) -> Result<(), MorseError> {
+        self.finish_char(events)?;
+        match m_bit {
+            MorseBit::Dot | MorseBit::Dash | MorseBit::CharBreak => {}
+            MorseBit::WordBreak => self.symbol(events, MorseSymbol::Char(' '), Some(' ')),
+            MorseBit::LineBreak => {
+                self.verify()?;
+                emit(events, DecoderEvent::FrameEnd);
+                self.state = DecoderState::Processing;
+            }
+        }
+        Ok(())
+    }
+
+    // drops the frame, after putting where it got to in `e`
+    #[cold]
+    #[inline(never)]
+    fn fail(&mut self, e: MorseError) -> MorseError {
+        let e = e
+            .at(Position {
+                bit: self.bits,
+                symbol: self.symbols,
+            })
+            .with_partial(self.recent.iter().copied());
+        self.reset();
+        e
+    }
+
+    fn finish_char(&mut self, events: &mut DecoderEvents) -> Result<(), MorseError> {
+        if self.cursor.is_empty() {
+            return Ok(());
+        }
+
+        let latin = match self.cursor.symbol_in(&DECODE_TREE) {
+            Some(MorseSymbol::Char(c)) => Some(c),
+            _ => None,
+        };
+        match (self.cursor.take()?, self.active) {
+            (MorseSymbol::Prosign(Prosign::DO), active) if active != Alphabet::Wabun => {
+                self.shift(Alphabet::Wabun);
+            }
+            (MorseSymbol::Prosign(Prosign::SN), Alphabet::Wabun) => {
+                self.shift(self.alphabet.shift_out());
+            }
+            (symbol, _) => self.symbol(events, symbol, latin),
+        }
+        Ok(())
+    }
+
+    fn shift(&mut self, alphabet: Alphabet) {
+        self.active = alphabet;
+        self.cursor = alphabet.tree().cursor();
+    }
+
+    // passes a symbol on once it's too far from the end to be the trailer
+    fn symbol(&mut self, events: &mut DecoderEvents, symbol: MorseSymbol, latin: Option<char>) {
+        self.symbols += 1;
+        if let MorseSymbol::Char(c) = symbol {
+            if self.recent.is_full() {
+                self.recent.pop_front();
+            }
+            self.recent.push_back(c).expect("room was just made");
+        }
+
+        if self.checksum == Checksum::None {
+            emit(events, DecoderEvent::Symbol(symbol));
+            return;
+        }
+
+        if self.held.len() == self.checksum.digits() {
+            let (released, _) = self.held.pop_front().expect("the trailer is never empty");
+            // prosigns are never sent by the encoder, so they aren't covered
+            if let MorseSymbol::Char(c) = released {
+                self.digest.update_char(c);
+            }
+            emit(events, DecoderEvent::Symbol(released));
+        }
+        self.held
+            .push_back((symbol, latin))
+            .expect("a symbol was just released");
+    }
+
+    fn verify(&mut self) -> Result<(), MorseError> {
+        if self.checksum == Checksum::None {
+            return Ok(());
+        }
+
+        // a trailer that isn't even hex can't be compared with anything. it
+        // was keyed in latin, so it's read by its patterns, whatever letters
+        // they make in the active alphabet
+        let mut expected = 0;
+        for (_, latin) in &self.held {
+            let digit = latin
+                .and_then(|c| c.to_digit(16))
+                .ok_or(ErrorKind::UnknownMorseSequence)?;
+            expected = expected << 4 | digit as u16;
+        }
+        if self.held.len() < self.checksum.digits() {
+            return Err(ErrorKind::UnknownMorseSequence.into());
+        }
+
+        let actual = self.digest.value();
+        if expected != actual {
+            return Err(ErrorKind::ChecksumMismatch { expected, actual }.into());
+        }
+        Ok(())
+    }
+}
+
+fn emit(events: &mut DecoderEvents, event: DecoderEvent) {
+    events
+        .push(event)
+        .expect("a bit never produces more than two events");
+}
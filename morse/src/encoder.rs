@@ -0,0 +1,220 @@
+use core::iter::{FusedIterator, Peekable};
+
+use crate::{
+    Alphabet, AnyLineCode, Bit, Checksum, Digest, LineCode, LinkConfig, MorseBit, MorseError,
+    Prosign, UnaryCode, fold, voicing_mark,
+};
+
+/// lazily turns a stream of chars into line bits, one char at a time, so a
+/// message of any length can be sent without buffering it first.
+///
+/// every char is followed by a `CharBreak`, except the last one which gets a
+/// `LineBreak` to close the message. an empty message is just the `LineBreak`.
+/// an unsupported char ends the stream with an error.
+///
+/// with a [`Checksum`] set, its hex digits are sent as the last chars of the
+/// message, worked out over the uppercase text the receiver will decode. they
+/// are always keyed in latin.
+///
+/// chars are looked up in the configured [`Alphabet`], and a run of kana in
+/// any other alphabet is shifted into wabun and back automatically.
+pub struct MorseEncoder<I: Iterator<Item = char>, C: LineCode = UnaryCode> {
+    chars: Peekable<I>,
+    code: C,
+    digest: Digest,
+    // trailer digits still to send, `None` until the message runs out
+    trailer: Option<usize>,
+    alphabet: Alphabet,
+    active: Alphabet,
+    // voicing mark owed after the kana just sent
+    mark: Option<char>,
+    // char waiting behind the shift prosign being sent
+    shifted: Option<char>,
+    pattern: &'static [MorseBit],
+    delimiter: Option<MorseBit>,
+    bits: Option<C::Bits>,
+    // the `LineBreak` has been handed out
+    closed: bool,
+    failed: bool,
+}
+
+impl<I: Iterator<Item = char>> MorseEncoder<I> {
+    pub fn new(chars: impl IntoIterator<IntoIter = I>) -> Self {
+        Self::with_line_code(chars, UnaryCode::default())
+    }
+}
+
+impl<I: Iterator<Item = char>> MorseEncoder<I, AnyLineCode> {
+    /// keys `chars` with the line code and checksum from `config`
+    pub fn with_config(chars: impl IntoIterator<IntoIter = I>, config: &LinkConfig) -> Self {
+        Self::with_line_code(chars, config.line_code)
+            .with_checksum(config.checksum)
+            .with_alphabet(config.alphabet)
+    }
+}
+
+impl<I: Iterator<Item = char>, C: LineCode> MorseEncoder<I, C> {
+    pub fn with_line_code(chars: impl IntoIterator<IntoIter = I>, code: C) -> Self {
+        Self {
+            chars: chars.into_iter().peekable(),
+            code,
+            digest: Checksum::None.digest(),
+            trailer: None,
+            alphabet: Alphabet::Latin,
+            active: Alphabet::Latin,
+            mark: None,
+            shifted: None,
+            pattern: &[],
+            delimiter: None,
+            bits: None,
+            closed: false,
+            failed: false,
+        }
+    }
+
+    pub fn with_checksum(mut self, checksum: Checksum) -> Self {
+        self.digest = checksum.digest();
+        self
+    }
+
+    /// the alphabet the receiver starts every frame in
+    pub fn with_alphabet(mut self, alphabet: Alphabet) -> Self {
+        self.alphabet = alphabet;
+        self.active = alphabet;
+        self
+    }
+
+    fn next_char(&mut self) -> Option<char> {
+        if let Some(mark) = self.mark.take() {
+            self.digest.update_char(mark);
+            return Some(mark);
+        }
+
+        if self.trailer.is_none()
+            && let Some(c) = self.chars.next()
+        {
+            let c = match voicing_mark(fold(c)) {
+                Some((plain, mark)) => {
+                    self.mark = Some(mark);
+                    plain
+                }
+                None => fold(c),
+            };
+            self.digest.update_char(c);
+            return Some(c);
+        }
+
+        let left = self.trailer.get_or_insert(self.digest_digits());
+        *left = left.checked_sub(1)?;
+        let nibble = (self.digest.value() >> (*left * 4)) & 0xf;
+        char::from_digit(nibble as u32, 16).map(|c| c.to_ascii_uppercase())
+    }
+
+    fn has_next_char(&mut self) -> bool {
+        if self.mark.is_some() {
+            return true;
+        }
+        match self.trailer {
+            None => self.chars.peek().is_some() || self.digest_digits() > 0,
+            Some(left) => left > 0,
+        }
+    }
+
+    fn digest_digits(&self) -> usize {
+        self.digest.checksum().digits()
+    }
+
+    fn next_morse_bit(&mut self) -> Option<Result<MorseBit, MorseError>> {
+        if self.pattern.is_empty() {
+            if let Some(delimiter) = self.delimiter.take() {
+                return Some(Ok(delimiter));
+            }
+
+            let c = match self.shifted.take() {
+                Some(c) => c,
+                None => match self.next_char() {
+                    Some(c) => c,
+                    // nothing was sent, but the line still has to be closed
+                    None if !self.closed => {
+                        self.closed = true;
+                        return Some(Ok(MorseBit::LineBreak));
+                    }
+                    None => return None,
+                },
+            };
+
+            let pattern = if self.trailer.is_some() {
+                Alphabet::Latin.char_sequence(c)
+            } else if let Some(prosign) = self.shift(c) {
+                self.shifted = Some(c);
+                self.pattern = prosign.sequence();
+                self.delimiter = Some(MorseBit::CharBreak);
+                return self.next_morse_bit();
+            } else {
+                self.active.char_sequence(c)
+            };
+            self.pattern = match pattern {
+                Ok(pattern) => pattern,
+                Err(e) => return Some(Err(e)),
+            };
+            self.delimiter = Some(if self.has_next_char() {
+                MorseBit::CharBreak
+            } else {
+                self.closed = true;
+                MorseBit::LineBreak
+            });
+        }
+
+        let (m_bit, rest) = self.pattern.split_first()?;
+        self.pattern = rest;
+        Some(Ok(*m_bit))
+    }
+
+    // the prosign to send before `c` if it needs the line shifted into or out
+    // of wabun
+    fn shift(&mut self, c: char) -> Option<Prosign> {
+        if self.active.char_sequence(c).is_ok() {
+            return None;
+        }
+
+        let (target, prosign) = match self.active {
+            Alphabet::Wabun => (self.alphabet.shift_out(), Prosign::SN),
+            _ => (Alphabet::Wabun, Prosign::DO),
+        };
+        target.char_sequence(c).ok()?;
+        self.active = target;
+        Some(prosign)
+    }
+}
+
+impl<'a> From<&'a str> for MorseEncoder<core::str::Chars<'a>> {
+    fn from(value: &'a str) -> Self {
+        MorseEncoder::new(value.chars())
+    }
+}
+
+impl<I: Iterator<Item = char>, C: LineCode> Iterator for MorseEncoder<I, C> {
+    type Item = Result<Bit, MorseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        loop {
+            if let Some(bit) = self.bits.as_mut().and_then(Iterator::next) {
+                return Some(Ok(bit));
+            }
+
+            match self.next_morse_bit()? {
+                Ok(m_bit) => self.bits = Some(self.code.encode(m_bit)),
+                Err(e) => {
+                    self.failed = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+    }
+}
+
+impl<I: Iterator<Item = char>, C: LineCode> FusedIterator for MorseEncoder<I, C> {}
@@ -0,0 +1,222 @@
+use core::fmt;
+
+use crate::{Bit, BitSequece, MAX_DEPTH, MorseBit, Notation};
+
+/// how much of the decoded text an error keeps, in bytes. kept small since
+/// errors are returned by value from every push
+pub const PARTIAL_LEN: usize = 48;
+
+/// what went wrong, [`MorseError`] adds where
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ErrorKind {
+    UnknownBitSequence,
+    UnknownMorseSequence,
+    UnsupportedChar(char),
+    FullBuffer,
+    /// the frame's trailer didn't match what was actually received
+    ChecksumMismatch {
+        expected: u16,
+        actual: u16,
+    },
+    /// more of the frame was corrupted than its error correction can fix
+    TooManyErrors,
+    /// a frame from a protocol version we don't speak
+    UnsupportedVersion(u8),
+    /// a frame got through its checks but its header or compressed payload
+    /// makes no sense
+    MalformedFrame,
+    /// a frame wasn't sealed with the receiver's key, or came sealed to a
+    /// receiver without one
+    AuthenticationFailed,
+    /// a sealed frame that was already let through once, or is older than
+    /// one that was
+    ReplayedFrame,
+    /// a reed-solomon parity length the code can't do
+    UnsupportedParity(u8),
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorKind::UnknownBitSequence => f.write_str("unknown bit sequence"),
+            ErrorKind::UnknownMorseSequence => f.write_str("unknown morse sequence"),
+            ErrorKind::UnsupportedChar(c) => write!(f, "unsupported char {c:?}"),
+            ErrorKind::FullBuffer => f.write_str("buffer full"),
+            ErrorKind::ChecksumMismatch { expected, actual } => {
+                write!(
+                    f,
+                    "checksum mismatch, expected {expected:04x} got {actual:04x}"
+                )
+            }
+            ErrorKind::TooManyErrors => f.write_str("too many errors to correct"),
+            ErrorKind::UnsupportedVersion(version) => {
+                write!(f, "unsupported protocol version {version}")
+            }
+            ErrorKind::MalformedFrame => f.write_str("malformed frame"),
+            ErrorKind::AuthenticationFailed => f.write_str("frame failed authentication"),
+            ErrorKind::ReplayedFrame => f.write_str("replayed frame"),
+            ErrorKind::UnsupportedParity(parity) => {
+                write!(f, "unsupported parity length {parity}")
+            }
+        }
+    }
+}
+
+impl core::error::Error for ErrorKind {}
+
+/// where in a frame an error happened, counted from the first bit after the
+/// preamble
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Position {
+    /// line bits read, the one that failed included
+    pub bit: u64,
+    /// symbols decoded before it, or bytes on a byte link
+    pub symbol: usize,
+}
+
+/// what the decoder was looking at when it gave up
+#[derive(Clone, PartialEq, Debug, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Offending {
+    /// line bits of the element being read, the last ones if there were more
+    Bits(BitSequece),
+    /// elements of the char being read, the one that didn't fit included
+    Elements(heapless::Vec<MorseBit, { MAX_DEPTH + 1 }>),
+}
+
+impl fmt::Display for Offending {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Offending::Bits(bits) => Notation(bits).fmt(f),
+            Offending::Elements(elements) => Notation(elements).fmt(f),
+        }
+    }
+}
+
+/// an [`ErrorKind`] plus whatever the failing decoder knew about it. errors
+/// that don't come out of a frame in progress only carry the kind.
+///
+/// the display form reads like
+/// `unknown bit sequence at bit 57, symbol 4 reading HHHHHHHL after "HELL"`
+#[derive(Clone, PartialEq, Debug, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MorseError {
+    kind: ErrorKind,
+    position: Option<Position>,
+    sequence: Option<Offending>,
+    partial: heapless::String<PARTIAL_LEN>,
+}
+
+impl MorseError {
+    pub const fn new(kind: ErrorKind) -> Self {
+        Self {
+            kind,
+            position: None,
+            sequence: None,
+            partial: heapless::String::new(),
+        }
+    }
+
+    pub const fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    pub const fn position(&self) -> Option<Position> {
+        self.position
+    }
+
+    /// the bits or elements that couldn't be decoded
+    pub const fn sequence(&self) -> Option<&Offending> {
+        self.sequence.as_ref()
+    }
+
+    /// the end of the text decoded so far in the frame, empty if there was none
+    pub fn partial(&self) -> &str {
+        &self.partial
+    }
+
+    /// where it happened, a position that's already set is kept since the
+    /// innermost decoder knows best
+    pub fn at(mut self, position: Position) -> Self {
+        self.position.get_or_insert(position);
+        self
+    }
+
+    /// keeps the last [`crate::BIT_SEQUENCE_LEN`] of `bits`
+    pub fn with_bits(mut self, bits: impl IntoIterator<Item = Bit>) -> Self {
+        let mut tail = BitSequece::new();
+        for bit in bits {
+            if tail.is_full() {
+                tail.remove(0);
+            }
+            tail.push(bit).expect("room was just made");
+        }
+        self.sequence.get_or_insert(Offending::Bits(tail));
+        self
+    }
+
+    /// keeps the last `MAX_DEPTH + 1` of `elements`
+    pub fn with_elements(mut self, elements: &[MorseBit]) -> Self {
+        let tail = &elements[elements.len().saturating_sub(MAX_DEPTH + 1)..];
+        let elements = heapless::Vec::from_slice(tail).expect("the tail always fits");
+        self.sequence.get_or_insert(Offending::Elements(elements));
+        self
+    }
+
+    /// the text decoded so far, as much of it as fits in [`PARTIAL_LEN`]
+    pub fn with_partial(mut self, text: impl IntoIterator<Item = char>) -> Self {
+        if self.partial.is_empty() {
+            for c in text {
+                if self.partial.push(c).is_err() {
+                    break;
+                }
+            }
+        }
+        self
+    }
+}
+
+impl From<ErrorKind> for MorseError {
+    fn from(kind: ErrorKind) -> Self {
+        Self::new(kind)
+    }
+}
+
+impl fmt::Display for MorseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.kind.fmt(f)?;
+        if let Some(Position { bit, symbol }) = self.position {
+            write!(f, " at bit {bit}, symbol {symbol}")?;
+        }
+        if let Some(sequence) = &self.sequence {
+            write!(f, " reading {sequence}")?;
+        }
+        if !self.partial.is_empty() {
+            write!(f, " after {:?}", self.partial.as_str())?;
+        }
+        Ok(())
+    }
+}
+
+impl core::error::Error for MorseError {}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for MorseError {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "{}", self.kind);
+        if let Some(Position { bit, symbol }) = self.position {
+            defmt::write!(f, " at bit {}, symbol {}", bit, symbol);
+        }
+        if let Some(sequence) = &self.sequence {
+            defmt::write!(f, " reading {}", sequence);
+        }
+        if !self.partial.is_empty() {
+            defmt::write!(f, " after {=str}", self.partial.as_str());
+        }
+    }
+}
@@ -0,0 +1,251 @@
+use core::iter::FusedIterator;
+
+use heapless::Deque;
+
+use crate::reed_solomon::{BLOCK_LEN, MAX_PARITY, ReedSolomon};
+use crate::{ErrorKind, MorseError};
+
+/// forward error correction for the byte stream, so a few flipped bits on a
+/// noisy link get fixed instead of throwing the whole frame away.
+///
+/// it sits between the payload (checksum included) and the line framing, so
+/// the checksum is checked on what came out of the correction.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Fec {
+    #[default]
+    None,
+    /// every nibble gets its own byte holding a hamming(7,4) codeword, which
+    /// doubles the payload but fixes any single flipped bit in each. good for
+    /// short payloads
+    Hamming74,
+    /// reed-solomon blocks of up to 255 bytes, each with `parity` check bytes
+    /// that fix up to `parity / 2` bad bytes anywhere in the block. cheaper
+    /// than hamming on longer payloads, and it shrugs off bursts. built with
+    /// [`Fec::reed_solomon`]
+    ReedSolomon { parity: Parity },
+}
+
+/// check bytes per reed-solomon block, always one [`ReedSolomon::new`] takes
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(transparent))]
+pub struct Parity(u8);
+
+impl Parity {
+    /// fails with [`ErrorKind::UnsupportedParity`] unless `parity` is between
+    /// 1 and [`MAX_PARITY`]
+    pub const fn new(parity: u8) -> Result<Self, MorseError> {
+        if ReedSolomon::supports(parity as usize) {
+            Ok(Self(parity))
+        } else {
+            Err(MorseError::new(ErrorKind::UnsupportedParity(parity)))
+        }
+    }
+
+    // for presets, where a bad parity is a bug
+    pub(crate) const fn fixed(parity: u8) -> Self {
+        assert!(
+            ReedSolomon::supports(parity as usize),
+            "unsupported parity length"
+        );
+        Self(parity)
+    }
+
+    pub const fn get(self) -> u8 {
+        self.0
+    }
+}
+
+// a parity reed-solomon can't do is refused here, not when the link starts
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Parity {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let parity = u8::deserialize(deserializer)?;
+        Parity::new(parity).map_err(serde::de::Error::custom)
+    }
+}
+
+impl Fec {
+    /// [`Fec::ReedSolomon`] with `parity` check bytes per block, see
+    /// [`Parity::new`]
+    pub fn reed_solomon(parity: u8) -> Result<Self, MorseError> {
+        Ok(Fec::ReedSolomon {
+            parity: Parity::new(parity)?,
+        })
+    }
+
+    /// wraps a byte stream, adding the redundancy as it goes
+    pub fn encode<I: IntoIterator<Item = u8>>(self, bytes: I) -> FecEncoder<I::IntoIter> {
+        FecEncoder {
+            bytes: bytes.into_iter(),
+            code: match self {
+                Fec::ReedSolomon { parity } => Some(ReedSolomon::new(parity.get() as usize)),
+                Fec::None | Fec::Hamming74 => None,
+            },
+            fec: self,
+            remainder: [0; MAX_PARITY],
+            block: 0,
+            pending: Deque::new(),
+        }
+    }
+
+    /// length of `len` payload bytes once encoded
+    pub const fn encoded_len(self, len: usize) -> usize {
+        match self {
+            Fec::None => len,
+            Fec::Hamming74 => len * 2,
+            Fec::ReedSolomon { parity } => {
+                let parity = parity.get() as usize;
+                len + len.div_ceil(BLOCK_LEN - parity) * parity
+            }
+        }
+    }
+
+    /// corrects an encoded buffer in place, leaving the payload at the front.
+    /// returns the payload length
+    pub fn decode(self, buf: &mut [u8]) -> Result<usize, MorseError> {
+        match self {
+            Fec::None => Ok(buf.len()),
+            Fec::Hamming74 => {
+                if !buf.len().is_multiple_of(2) {
+                    return Err(ErrorKind::TooManyErrors.into());
+                }
+                for i in 0..buf.len() / 2 {
+                    let lo = hamming_decode(buf[2 * i]);
+                    let hi = hamming_decode(buf[2 * i + 1]);
+                    buf[i] = hi << 4 | lo;
+                }
+                Ok(buf.len() / 2)
+            }
+            Fec::ReedSolomon { parity } => {
+                let code = ReedSolomon::new(parity.get() as usize);
+                let (mut read, mut written) = (0, 0);
+                while read < buf.len() {
+                    let end = (read + BLOCK_LEN).min(buf.len());
+                    code.decode(&mut buf[read..end])?;
+
+                    let data = end - read - code.parity();
+                    buf.copy_within(read..read + data, written);
+                    read = end;
+                    written += data;
+                }
+                Ok(written)
+            }
+        }
+    }
+}
+
+/// hamming(7,4) codeword for the low nibble of `nibble`, laid out as
+/// `p1 p2 d1 p3 d2 d3 d4` from the lowest bit up. the top bit is always clear
+pub const fn hamming_encode(nibble: u8) -> u8 {
+    let d = [
+        nibble & 1,
+        nibble >> 1 & 1,
+        nibble >> 2 & 1,
+        nibble >> 3 & 1,
+    ];
+    let p1 = d[0] ^ d[1] ^ d[3];
+    let p2 = d[0] ^ d[2] ^ d[3];
+    let p3 = d[1] ^ d[2] ^ d[3];
+    p1 | p2 << 1 | d[0] << 2 | p3 << 3 | d[1] << 4 | d[2] << 5 | d[3] << 6
+}
+
+/// the nibble in a hamming(7,4) codeword, with up to one flipped bit fixed.
+/// the top bit is ignored
+pub const fn hamming_decode(code: u8) -> u8 {
+    let b = [
+        code & 1,
+        code >> 1 & 1,
+        code >> 2 & 1,
+        code >> 3 & 1,
+        code >> 4 & 1,
+        code >> 5 & 1,
+        code >> 6 & 1,
+    ];
+
+    // the syndrome spells out the 1 based position of a flipped bit
+    let s1 = b[0] ^ b[2] ^ b[4] ^ b[6];
+    let s2 = b[1] ^ b[2] ^ b[5] ^ b[6];
+    let s3 = b[3] ^ b[4] ^ b[5] ^ b[6];
+    let syndrome = s1 | s2 << 1 | s3 << 2;
+
+    let code = if syndrome == 0 {
+        code
+    } else {
+        code ^ 1 << (syndrome - 1)
+    };
+    (code >> 2 & 1) | (code >> 3 & 0b1110)
+}
+
+pub struct FecEncoder<I> {
+    bytes: I,
+    fec: Fec,
+    code: Option<ReedSolomon>,
+    // parity being built up for the current reed-solomon block
+    remainder: [u8; MAX_PARITY],
+    block: usize,
+    // bytes owed before the next one is pulled, the high hamming nibble or a
+    // block's parity
+    pending: Deque<u8, MAX_PARITY>,
+}
+
+impl<I> FecEncoder<I> {
+    pub(crate) fn get_mut(&mut self) -> &mut I {
+        &mut self.bytes
+    }
+
+    pub(crate) fn into_inner(self) -> I {
+        self.bytes
+    }
+
+    fn flush(&mut self) {
+        let Some(code) = &self.code else {
+            return;
+        };
+        for byte in &self.remainder[..code.parity()] {
+            self.pending.push_back(*byte).expect("parity always fits");
+        }
+        self.remainder = [0; MAX_PARITY];
+        self.block = 0;
+    }
+}
+
+impl<I: Iterator<Item = u8>> Iterator for FecEncoder<I> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        if let Some(byte) = self.pending.pop_front() {
+            return Some(byte);
+        }
+
+        let Some(byte) = self.bytes.next() else {
+            // close off a short last block
+            if self.block > 0 {
+                self.flush();
+            }
+            return self.pending.pop_front();
+        };
+
+        match (self.fec, &self.code) {
+            (Fec::Hamming74, _) => {
+                self.pending
+                    .push_back(hamming_encode(byte >> 4))
+                    .expect("pending was empty");
+                Some(hamming_encode(byte & 0xf))
+            }
+            (Fec::ReedSolomon { .. }, Some(code)) => {
+                code.feed(&mut self.remainder, byte);
+                self.block += 1;
+                if self.block == code.data_len() {
+                    self.flush();
+                }
+                Some(byte)
+            }
+            _ => Some(byte),
+        }
+    }
+}
+
+impl<I: FusedIterator<Item = u8>> FusedIterator for FecEncoder<I> {}
@@ -0,0 +1,452 @@
+use core::iter::{Chain, Copied};
+
+use crate::{
+    Bit, Buffer, ByteDecoder, ByteEncoder, ByteEvent, Checksum, Compress, DecoderState, Decompress,
+    ErrorKind, Fec, LinkConfig, MorseError, Preamble, PreambleMatch, compressed_len,
+};
+
+/// bumped whenever the layout below changes, a receiver drops frames from any
+/// other version instead of guessing at them
+pub const PROTOCOL_VERSION: u8 = 1;
+
+/// version, type, sequence and length
+pub const HEADER_LEN: usize = 6;
+
+/// every frame is checked with this, it's part of the protocol
+pub const FRAME_CHECKSUM: Checksum = Checksum::Crc16;
+
+/// header flag for a payload squeezed with [`Compress`], its length is the
+/// compressed one
+pub const FLAG_COMPRESSED: u8 = 0x1;
+
+/// header flag for a payload sealed with a `FrameKey`, its length includes
+/// the session in front and the tag behind. compression comes first,
+/// encryption leaves nothing to squeeze
+pub const FLAG_ENCRYPTED: u8 = 0x2;
+
+/// what the payload of a frame is
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MessageType {
+    /// utf-8 text
+    Text = 0,
+    /// raw bytes, sensor readings, files, whatever
+    Data = 1,
+    /// confirms the frame with the same sequence number got through
+    Ack = 2,
+    /// asks for an ack, the payload is usually empty
+    Ping = 3,
+}
+
+impl TryFrom<u8> for MessageType {
+    type Error = MorseError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(MessageType::Text),
+            1 => Ok(MessageType::Data),
+            2 => Ok(MessageType::Ack),
+            3 => Ok(MessageType::Ping),
+            _ => Err(ErrorKind::MalformedFrame.into()),
+        }
+    }
+}
+
+/// everything in front of the payload. multi byte fields are big endian:
+///
+/// | bytes | field                                             |
+/// |-------|---------------------------------------------------|
+/// | 1     | version                                           |
+/// | 1     | message type in the low nibble, flags in the high |
+/// | 2     | sequence number                                   |
+/// | 2     | payload length                                    |
+///
+/// on the line it comes right after the start sequence, and the payload is
+/// followed by a [`FRAME_CHECKSUM`] over header and payload together. all of
+/// it is sent with [`ByteEncoder`].
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FrameHeader {
+    pub version: u8,
+    pub message_type: MessageType,
+    /// only the low nibble is sent
+    pub flags: u8,
+    pub sequence: u16,
+    pub length: u16,
+}
+
+impl FrameHeader {
+    pub const fn new(message_type: MessageType, sequence: u16, length: u16) -> Self {
+        Self {
+            version: PROTOCOL_VERSION,
+            message_type,
+            flags: 0,
+            sequence,
+            length,
+        }
+    }
+
+    pub const fn to_bytes(&self) -> [u8; HEADER_LEN] {
+        let [sequence_hi, sequence_lo] = self.sequence.to_be_bytes();
+        let [length_hi, length_lo] = self.length.to_be_bytes();
+        [
+            self.version,
+            (self.flags & 0xf) << 4 | self.message_type as u8,
+            sequence_hi,
+            sequence_lo,
+            length_hi,
+            length_lo,
+        ]
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MorseError> {
+        let Some(header) = bytes.first_chunk::<HEADER_LEN>() else {
+            return Err(ErrorKind::MalformedFrame.into());
+        };
+
+        let version = header[0];
+        if version != PROTOCOL_VERSION {
+            return Err(ErrorKind::UnsupportedVersion(version).into());
+        }
+
+        Ok(Self {
+            version,
+            message_type: MessageType::try_from(header[1] & 0xf)?,
+            flags: header[1] >> 4,
+            sequence: u16::from_be_bytes([header[2], header[3]]),
+            length: u16::from_be_bytes([header[4], header[5]]),
+        })
+    }
+}
+
+type FrameBytes<'a> = Chain<core::array::IntoIter<u8, HEADER_LEN>, Body<'a>>;
+
+// the payload as it goes on the line
+enum Body<'a> {
+    Plain(Copied<core::slice::Iter<'a, u8>>),
+    Compressed(Compress<'a>),
+}
+
+impl Iterator for Body<'_> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        match self {
+            Body::Plain(bytes) => bytes.next(),
+            Body::Compressed(bytes) => bytes.next(),
+        }
+    }
+}
+
+/// streams one whole frame as line bits, everything after the start sequence,
+/// which is sent first like with every other encoder
+pub struct FrameEncoder<'a> {
+    bits: ByteEncoder<FrameBytes<'a>>,
+}
+
+impl<'a> FrameEncoder<'a> {
+    /// fails with [`ErrorKind::FullBuffer`] if the payload is too long for
+    /// its length field
+    pub fn new(
+        message_type: MessageType,
+        sequence: u16,
+        payload: &'a [u8],
+    ) -> Result<Self, MorseError> {
+        let length = u16::try_from(payload.len()).map_err(|_| ErrorKind::FullBuffer)?;
+        Ok(Self::with_header(
+            FrameHeader::new(message_type, sequence, length),
+            payload,
+        ))
+    }
+
+    /// [`FrameEncoder::new`] with the payload compressed, as long as that
+    /// makes it shorter. the header says which it was, so the receiver needs
+    /// no setting for it.
+    /// fails with [`ErrorKind::FullBuffer`] if even the compressed payload is
+    /// too long for its length field
+    pub fn compressed(
+        message_type: MessageType,
+        sequence: u16,
+        payload: &'a [u8],
+    ) -> Result<Self, MorseError> {
+        let len = compressed_len(payload);
+        if len >= payload.len() {
+            return Self::new(message_type, sequence, payload);
+        }
+
+        let length = u16::try_from(len).map_err(|_| ErrorKind::FullBuffer)?;
+        let mut header = FrameHeader::new(message_type, sequence, length);
+        header.flags |= FLAG_COMPRESSED;
+        Ok(Self::with_header(header, payload))
+    }
+
+    /// sends `header` as is, its length is trusted to match `payload`, after
+    /// compression if the header has [`FLAG_COMPRESSED`]
+    pub fn with_header(header: FrameHeader, payload: &'a [u8]) -> Self {
+        let body = if header.flags & FLAG_COMPRESSED != 0 {
+            Body::Compressed(Compress::new(payload))
+        } else {
+            Body::Plain(payload.iter().copied())
+        };
+        Self::with_body(header, body)
+    }
+
+    /// encrypts `payload` with `key` in `buffer`, which has to fit it, the
+    /// session and the tag. a header asking for [`FLAG_COMPRESSED`] gets it
+    /// whenever that makes the payload shorter, and [`FLAG_ENCRYPTED`] is
+    /// always set. `session` has to be new whenever `header`'s sequence
+    /// starts over, see [`crate::FrameKey`].
+    /// fails with [`ErrorKind::FullBuffer`] if the buffer or the length field
+    /// is too small
+    #[cfg(feature = "aead")]
+    pub fn sealed(
+        mut header: FrameHeader,
+        session: u64,
+        payload: &[u8],
+        key: &crate::FrameKey,
+        buffer: &'a mut [u8],
+    ) -> Result<Self, MorseError> {
+        let compressed = compressed_len(payload);
+        let len = if header.flags & FLAG_COMPRESSED != 0 && compressed < payload.len() {
+            compressed
+        } else {
+            header.flags &= !FLAG_COMPRESSED;
+            payload.len()
+        };
+
+        let sealed = buffer
+            .get_mut(..crate::SESSION_LEN + len + crate::TAG_LEN)
+            .ok_or(ErrorKind::FullBuffer)?;
+        header.length = u16::try_from(sealed.len()).map_err(|_| ErrorKind::FullBuffer)?;
+        header.flags |= FLAG_ENCRYPTED;
+
+        let (prefix, rest) = sealed.split_at_mut(crate::SESSION_LEN);
+        prefix.copy_from_slice(&session.to_be_bytes());
+        let (body, tag) = rest.split_at_mut(len);
+        if header.flags & FLAG_COMPRESSED != 0 {
+            body.iter_mut()
+                .zip(Compress::new(payload))
+                .for_each(|(byte, compressed)| *byte = compressed);
+        } else {
+            body.copy_from_slice(payload);
+        }
+        tag.copy_from_slice(&key.seal(session, &header, body));
+
+        let sealed: &'a [u8] = sealed;
+        Ok(Self::with_body(header, Body::Plain(sealed.iter().copied())))
+    }
+
+    fn with_body(header: FrameHeader, body: Body<'a>) -> Self {
+        let bytes = header.to_bytes().into_iter().chain(body);
+
+        Self {
+            bits: ByteEncoder::new(bytes).with_checksum(FRAME_CHECKSUM),
+        }
+    }
+
+    pub fn with_fec(mut self, fec: Fec) -> Self {
+        self.bits = self.bits.with_fec(fec);
+        self
+    }
+}
+
+impl Iterator for FrameEncoder<'_> {
+    type Item = Bit;
+
+    fn next(&mut self) -> Option<Bit> {
+        self.bits.next()
+    }
+}
+
+impl core::iter::FusedIterator for FrameEncoder<'_> {}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FrameEvent {
+    FrameStart,
+    /// a whole frame passed its checks, [`FrameDecoder::payload`] holds the
+    /// payload
+    FrameEnd(FrameHeader),
+}
+
+/// the receiving half of [`FrameEncoder`], holding frames in `B` as they came
+/// off the line, so header, checksum and any error correction all have to
+/// fit.
+///
+/// a frame from another protocol version, with an unknown type, or whose
+/// length doesn't match what arrived is dropped with an error, and so is a
+/// compressed one that doesn't decompress.
+///
+/// with a key set, only frames sealed with it are let through, anything else
+/// fails with [`ErrorKind::AuthenticationFailed`]. without one, so do all
+/// encrypted frames. a sealed frame that doesn't move its session's sequence
+/// forward, or comes from one of the last [`crate::RETIRED_SESSIONS`]
+/// sessions the decoder moved on from, fails with
+/// [`ErrorKind::ReplayedFrame`]. resets keep that memory.
+#[derive(Clone, Debug)]
+pub struct FrameDecoder<B: Buffer<u8>> {
+    bytes: ByteDecoder<B>,
+    header: Option<FrameHeader>,
+    #[cfg(feature = "aead")]
+    key: Option<crate::FrameKey>,
+    #[cfg(feature = "aead")]
+    replay: crate::crypto::Replay,
+}
+
+impl<B: Buffer<u8>> Default for FrameDecoder<B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<B: Buffer<u8>> FrameDecoder<B> {
+    pub const fn new() -> Self {
+        Self {
+            bytes: ByteDecoder::new().with_checksum(FRAME_CHECKSUM),
+            header: None,
+            #[cfg(feature = "aead")]
+            key: None,
+            #[cfg(feature = "aead")]
+            replay: crate::crypto::Replay::new(),
+        }
+    }
+
+    /// listens for the preamble and error correction from `config`
+    pub fn with_config(config: &LinkConfig) -> Self {
+        Self::new()
+            .with_preamble(config.preamble)
+            .with_fec(config.fec)
+    }
+
+    pub fn with_fec(mut self, fec: Fec) -> Self {
+        self.bytes = self.bytes.with_fec(fec);
+        self
+    }
+
+    /// listens for `preamble` instead of the exact [`crate::START_SEQUENCE`]
+    pub fn with_preamble(mut self, preamble: Preamble) -> Self {
+        self.bytes = self.bytes.with_preamble(preamble);
+        self
+    }
+
+    /// only takes frames sealed with `key`, and decrypts them
+    #[cfg(feature = "aead")]
+    pub fn with_key(mut self, key: crate::FrameKey) -> Self {
+        self.key = Some(key);
+        self
+    }
+
+    /// where the current (or last) frame's preamble was found
+    pub fn preamble_match(&self) -> Option<PreambleMatch> {
+        self.bytes.preamble_match()
+    }
+
+    pub fn state(&self) -> DecoderState {
+        self.bytes.state()
+    }
+
+    /// header of the last frame that made it through
+    pub fn header(&self) -> Option<FrameHeader> {
+        self.header
+    }
+
+    /// payload of the last frame that made it through as it was on the line,
+    /// decrypted if it was sealed, empty while the next one is coming in
+    pub fn payload(&self) -> &[u8] {
+        match self.header {
+            Some(_) => &self.bytes.payload()[HEADER_LEN..],
+            None => &[],
+        }
+    }
+
+    /// [`FrameDecoder::payload`] as it was sent, decompressed if the header
+    /// says it was compressed
+    pub fn message(&self) -> Decompress<'_> {
+        match self.header {
+            Some(header) if header.flags & FLAG_COMPRESSED != 0 => {
+                Decompress::new(self.payload()).expect("checked when the frame ended")
+            }
+            _ => Decompress::plain(self.payload()),
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.bytes.reset();
+        self.header = None;
+    }
+
+    pub fn push(&mut self, bit: Bit) -> Result<Option<FrameEvent>, MorseError> {
+        match self.bytes.push(bit) {
+            Ok(Some(ByteEvent::FrameStart)) => {
+                self.header = None;
+                Ok(Some(FrameEvent::FrameStart))
+            }
+            Ok(Some(ByteEvent::FrameEnd)) => {
+                let header = self.check().inspect_err(|_| self.reset())?;
+                self.header = Some(header);
+                Ok(Some(FrameEvent::FrameEnd(header)))
+            }
+            Ok(Some(ByteEvent::Byte(_)) | None) => Ok(None),
+            Err(e) => {
+                self.header = None;
+                Err(e)
+            }
+        }
+    }
+
+    fn check(&mut self) -> Result<FrameHeader, MorseError> {
+        let frame = self.bytes.payload();
+        let header = FrameHeader::from_bytes(frame)?;
+        if frame.len() - HEADER_LEN != header.length as usize {
+            return Err(ErrorKind::MalformedFrame.into());
+        }
+        self.open(&header)?;
+        if header.flags & FLAG_COMPRESSED != 0 {
+            Decompress::new(&self.bytes.payload()[HEADER_LEN..])?;
+        }
+        Ok(header)
+    }
+
+    // decrypts a sealed payload in place and drops its session and tag
+    #[cfg(feature = "aead")]
+    fn open(&mut self, header: &FrameHeader) -> Result<(), MorseError> {
+        use crate::{SESSION_LEN, TAG_LEN};
+
+        let encrypted = header.flags & FLAG_ENCRYPTED != 0;
+        let key = match &self.key {
+            Some(key) if encrypted => key,
+            None if !encrypted => return Ok(()),
+            _ => return Err(ErrorKind::AuthenticationFailed.into()),
+        };
+
+        let frame = self.bytes.payload_mut();
+        let start = HEADER_LEN + SESSION_LEN;
+        let end = frame
+            .len()
+            .checked_sub(TAG_LEN)
+            .filter(|end| *end >= start)
+            .ok_or(ErrorKind::AuthenticationFailed)?;
+        let (session, sealed) = frame[HEADER_LEN..].split_at_mut(SESSION_LEN);
+        let session = u64::from_be_bytes((&*session).try_into().expect("split at its length"));
+        let (payload, tag) = sealed.split_at_mut(end - start);
+        let tag = (&*tag).try_into().expect("split off at the tag length");
+        key.open(session, header, payload, tag)?;
+        self.replay.check(session, header.sequence)?;
+        self.replay.accept(session, header.sequence);
+
+        frame.copy_within(start..end, HEADER_LEN);
+        frame.truncate(end - SESSION_LEN);
+        Ok(())
+    }
+
+    #[cfg(not(feature = "aead"))]
+    fn open(&mut self, header: &FrameHeader) -> Result<(), MorseError> {
+        match header.flags & FLAG_ENCRYPTED {
+            0 => Ok(()),
+            _ => Err(ErrorKind::AuthenticationFailed.into()),
+        }
+    }
+}
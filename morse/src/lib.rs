@@ -1,12 +1,75 @@
 #![no_std]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+mod alphabet;
+mod biphase;
+mod buffer;
+mod bytes;
+mod code_table;
+mod compress;
+mod crc;
+#[cfg(feature = "aead")]
+mod crypto;
+mod decoder;
+mod encoder;
+mod error;
+mod fec;
+mod frame;
+mod line_code;
+mod link;
+mod notation;
+mod packed;
+mod preamble;
+mod reed_solomon;
+mod sampler;
+mod soft;
+mod tree;
+
+pub use alphabet::{
+    ARABIC_TREE, Alphabet, CYRILLIC_TREE, GREEK_TREE, HEBREW_TREE, WABUN_TREE, fold, voicing_mark,
+};
+pub use biphase::{Biphase, BiphaseDecoder, BiphaseEncoder};
+pub use buffer::Buffer;
+pub use bytes::{ByteDecoder, ByteEncoder, ByteEvent};
+pub use code_table::{CodeTable, ENGLISH, ENGLISH_FREQUENCIES, MAX_CODE_SYMBOLS};
+pub use compress::{Compress, DICTIONARY, Decompress, compressed_len};
+pub use crc::{Checksum, Digest};
+#[cfg(feature = "aead")]
+pub use crypto::{FrameKey, KEY_LEN, RETIRED_SESSIONS, SESSION_LEN, TAG_LEN};
+pub use decoder::{DecoderEvent, DecoderEvents, DecoderState, MorseDecoder};
+pub use encoder::MorseEncoder;
+pub use error::{ErrorKind, MorseError, Offending, PARTIAL_LEN, Position};
+pub use fec::{Fec, FecEncoder, Parity, hamming_decode, hamming_encode};
+pub use frame::{
+    FLAG_COMPRESSED, FLAG_ENCRYPTED, FRAME_CHECKSUM, FrameDecoder, FrameEncoder, FrameEvent,
+    FrameHeader, HEADER_LEN, MessageType, PROTOCOL_VERSION,
+};
+pub use line_code::{
+    AnyLineCode, AnyLineDecoder, ItuCode, ItuDecoder, LineCode, LineDecoder, Pulse, UnaryCode,
+    UnaryDecoder,
+};
+pub use link::{BitClock, Framing, LinkConfig, TAIL_BITS, Transmission};
+pub use notation::{Notation, parse_bits, parse_morse};
+pub use packed::{PackedBits, PackedIter};
+pub use preamble::{MAX_PREAMBLE_LEN, Preamble, PreambleMatch, StartDetector};
+pub use reed_solomon::{BLOCK_LEN, MAX_PARITY, ReedSolomon};
+pub use sampler::Sampler;
+pub use soft::{Soft, TRACEBACK};
+pub use tree::{DECODE_TREE, MAX_DEPTH, MorseTree, TreeCursor};
+
 #[derive(Copy, Clone, PartialEq, Debug, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Bit {
     Hi,
     Lo,
 }
 
 #[derive(Copy, Clone, PartialEq, Debug, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum MorseBit {
     Dot,
     Dash,
@@ -15,39 +78,77 @@ pub enum MorseBit {
     LineBreak,
 }
 
-pub type BitSequece = heapless::Vec<Bit, 8>;
+/// procedural signals from ITU-R M.1677, sent as a single run of elements
+/// without a char break in between
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Prosign {
+    /// end of message
+    AR,
+    /// wait
+    AS,
+    /// break / new paragraph
+    BT,
+    /// error
+    HH,
+    /// starting signal
+    KA,
+    /// end of work
+    SK,
+    /// understood, also shifts back out of wabun
+    SN,
+    /// shifts into wabun, see [`Alphabet::Wabun`]
+    DO,
+}
+
+impl Prosign {
+    pub const ALL: [Prosign; 8] = [
+        Prosign::AR,
+        Prosign::AS,
+        Prosign::BT,
+        Prosign::HH,
+        Prosign::KA,
+        Prosign::SK,
+        Prosign::SN,
+        Prosign::DO,
+    ];
 
-pub type MorseBitSequence = heapless::Vec<MorseBit, 350>;
+    pub const fn sequence(self) -> &'static [MorseBit] {
+        use MorseBit::*;
+        match self {
+            Prosign::AR => &[Dot, Dash, Dot, Dash, Dot],
+            Prosign::AS => &[Dot, Dash, Dot, Dot, Dot],
+            Prosign::BT => &[Dash, Dot, Dot, Dot, Dash],
+            Prosign::HH => &[Dot, Dot, Dot, Dot, Dot, Dot, Dot, Dot],
+            Prosign::KA => &[Dash, Dot, Dash, Dot, Dash],
+            Prosign::SK => &[Dot, Dot, Dot, Dash, Dot, Dash],
+            Prosign::SN => &[Dot, Dot, Dot, Dash, Dot],
+            Prosign::DO => &[Dash, Dot, Dot, Dash, Dash, Dash],
+        }
+    }
+}
 
-#[derive(Debug)]
-pub enum MorseError {
-    UnknownBitSequence,
-    UnknownMorseSequence,
-    UnsupportedChar(char),
-    FullBuffer,
+/// anything that can come out of a single morse character slot
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MorseSymbol {
+    Char(char),
+    Prosign(Prosign),
 }
 
-// pub const TIME_STEP_MICROS: u64 = 1e3 as u64 / 2;
-// pub const TIME_STEP_MICROS: u64 = 1e3 as u64 / 2;
-// pub const TIME_STEP_MICROS: u64 = 1000;
-// pub const TIME_STEP_MICROS: u64 = 900;
-// pub const TIME_STEP_MICROS: u64 = 100;
-// pub const TIME_STEP_MICROS: u64 = 50;
-// pub const TIME_STEP_MICROS: u64 = 20;
-// pub const TIME_STEP_MICROS: u64 = 15;
-pub const TIME_STEP_MICROS: u64 = 11;
-// pub const TIME_STEP_MICROS: u64 = 10;
+pub const BIT_SEQUENCE_LEN: usize = 8;
+
+pub const MORSE_SEQUENCE_LEN: usize = 350;
 
-// pub const MSG: &str = "Surendra";
-// pub const MSG: &str = "suri.codes";
+/// elements a single char can take, a voiced kana is two patterns with a char
+/// break between them
+pub const CHAR_SEQUENCE_LEN: usize = 2 * MAX_DEPTH + 1;
 
-// pub const MSG: &str = "e";
-// pub const MSG: &str = "eeeeeeeeeeeeeeeeeeeeeeeeeee";
-// pub const MSG: &str = "eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee";
+pub type BitSequece<const N: usize = BIT_SEQUENCE_LEN> = heapless::Vec<Bit, N>;
 
-// pub const MSG: &str = "EEEEEEEEEEEEETTTTTTTTTTAAAAAAAAAOOOOOOOOOIIIIIIIINNNNNNNSSSSSSHHHHHHRRRRRRDDDDDDLLLLLCCCCUUUUMMMMWWWFFFGGGYYYPPPBBVVKKJJXQZ";
-// pub const MSG: &str = "Hello ESP32";
-pub const MSG: &str = "UCSC CSE 121 ABCDEFGHIJKLM NOPQRSTUVWXYZ 12345 67890";
+pub type MorseBitSequence<const N: usize = MORSE_SEQUENCE_LEN> = heapless::Vec<MorseBit, N>;
 
 pub const START_SEQUENCE: [Bit; 10] = [
     Bit::Hi,
@@ -65,92 +166,115 @@ pub const START_SEQUENCE: [Bit; 10] = [
 // gets picked up really easily, but corrupts data
 // pub const START_SEQUENCE: [Bit; 2] = [Bit::Hi, Bit::Hi];
 
+/// conversion to and from element patterns. the plain methods work in
+/// [`Alphabet::Latin`], the `_in` ones in whichever alphabet is active.
+/// patterns are collected into any [`Buffer`], a
+/// `MorseBitSequence<CHAR_SEQUENCE_LEN>` always fits
 pub trait MorseConversion {
-    fn to_morse_bit_sequence(&self) -> Result<MorseBitSequence, MorseError>;
-    fn from_morse_slice(sequence: &[MorseBit]) -> Result<Self, MorseError>
+    fn to_morse_bit_sequence_in<B: Buffer<MorseBit>>(
+        &self,
+        alphabet: Alphabet,
+    ) -> Result<B, MorseError>;
+    fn from_morse_slice_in(sequence: &[MorseBit], alphabet: Alphabet) -> Result<Self, MorseError>
     where
         Self: Sized;
-}
 
-impl MorseConversion for char {
-    fn to_morse_bit_sequence(&self) -> Result<MorseBitSequence, MorseError> {
-        use MorseBit::*;
-        let mut vec = MorseBitSequence::new();
-        let slice = {
-            let table_result = MORSE_TABLE[*self as usize];
-            if table_result.is_empty() {
-                match self {
-                    ' ' => &[WordBreak],
-                    '\n' => &[LineBreak],
-                    _ => return Err(MorseError::UnsupportedChar(*self)),
-                }
-            } else {
-                table_result
-            }
-        };
-        vec.extend_from_slice(slice)
-            .expect("should never run out of capacity");
-        Ok(vec)
+    fn to_morse_bit_sequence<B: Buffer<MorseBit>>(&self) -> Result<B, MorseError> {
+        self.to_morse_bit_sequence_in(Alphabet::Latin)
     }
 
-    fn from_morse_slice(sequence: &[MorseBit]) -> Result<Self, MorseError> {
-        let (_, c) = INVERSE_MORSE_TABLE
-            .iter()
-            .find(|(e, _)| *e == sequence)
-            .ok_or(MorseError::UnknownMorseSequence)?;
-        Ok(*c)
+    fn from_morse_slice(sequence: &[MorseBit]) -> Result<Self, MorseError>
+    where
+        Self: Sized,
+    {
+        Self::from_morse_slice_in(sequence, Alphabet::Latin)
     }
 }
 
-impl From<MorseBit> for BitSequece {
-    fn from(value: MorseBit) -> Self {
-        let mut vec = BitSequece::new();
-        use Bit::*;
-        match value {
-            MorseBit::Dot => vec.extend_from_slice(&[Lo]), // 2, 3
-            // MorseBit::Dot => vec.extend_from_slice(&[Hi, Lo]),
-            // MorseBit::Dash => vec.extend_from_slice(&[Hi, Lo]), // 2
-            MorseBit::Dash => vec.extend_from_slice(&[Hi, Hi, Lo]), // 3
-            // MorseBit::Dash => vec.extend_from_slice(&[Hi, Hi, Lo]),
-            // MorseBit::CharBreak => vec.extend_from_slice(&[Hi, Hi, Lo]), // 2
-            MorseBit::CharBreak => vec.extend_from_slice(&[Hi, Lo]), // 3
-            MorseBit::LineBreak => vec.extend_from_slice(&[Hi, Hi, Hi, Lo]),
-            MorseBit::WordBreak => vec.extend_from_slice(&[Hi, Hi, Hi, Hi, Lo]),
-        }
-        .expect("should be impossible to error");
-
-        vec
+/// looks up the pattern for `c`, ignoring ascii case. anything outside the
+/// table, including non-ascii chars, is reported as unsupported instead of
+/// panicking
+pub fn char_sequence(c: char) -> Result<&'static [MorseBit], MorseError> {
+    use MorseBit::*;
+    match c {
+        ' ' => Ok(&[WordBreak]),
+        '\n' => Ok(&[LineBreak]),
+        _ => MORSE_TABLE
+            .get(c.to_ascii_lowercase() as usize)
+            .copied()
+            .filter(|seq| !seq.is_empty())
+            .ok_or(ErrorKind::UnsupportedChar(c).into()),
     }
 }
 
-impl TryFrom<BitSequece> for MorseBit {
-    type Error = MorseError;
-    fn try_from(value: BitSequece) -> Result<Self, Self::Error> {
-        use Bit::*;
-        use MorseBit::*;
+/// encoding is case-insensitive. morse itself has no case, so decoding always
+/// yields the uppercase form of a letter. a voiced kana comes out as two
+/// chars, its plain kana and the voicing mark, with a char break between
+impl MorseConversion for char {
+    fn to_morse_bit_sequence_in<B: Buffer<MorseBit>>(
+        &self,
+        alphabet: Alphabet,
+    ) -> Result<B, MorseError> {
+        let (plain, mark) = match voicing_mark(fold(*self)) {
+            Some((plain, mark)) => (plain, Some(mark)),
+            None => (*self, None),
+        };
 
-        if value.starts_with(&[Lo]) && value.len() == 1 {
-            return Ok(Dot);
+        let mut seq = B::NEW;
+        for m_bit in alphabet.char_sequence(plain)? {
+            seq.push(*m_bit)?;
         }
-
-        if value.starts_with(&[Hi, Lo]) && value.len() == 2 {
-            return Ok(CharBreak);
-            // return Ok(Dash);
+        if let Some(mark) = mark {
+            seq.push(MorseBit::CharBreak)?;
+            for m_bit in alphabet.char_sequence(mark)? {
+                seq.push(*m_bit)?;
+            }
         }
-        if value.starts_with(&[Hi, Hi, Lo]) && value.len() == 3 {
-            return Ok(Dash);
-            // return Ok(CharBreak);
+        Ok(seq)
+    }
+
+    fn from_morse_slice_in(sequence: &[MorseBit], alphabet: Alphabet) -> Result<Self, MorseError> {
+        match alphabet.decode(sequence)? {
+            MorseSymbol::Char(c) => Ok(c),
+            MorseSymbol::Prosign(_) => Err(ErrorKind::UnknownMorseSequence.into()),
         }
-        if value.starts_with(&[Hi, Hi, Hi, Lo]) && value.len() == 4 {
-            // return Ok(WordBreak);
-            return Ok(LineBreak);
+    }
+}
+
+impl MorseConversion for Prosign {
+    fn to_morse_bit_sequence_in<B: Buffer<MorseBit>>(&self, _: Alphabet) -> Result<B, MorseError> {
+        let mut seq = B::NEW;
+        for m_bit in self.sequence() {
+            seq.push(*m_bit)?;
         }
-        if value.starts_with(&[Hi, Hi, Hi, Hi, Lo]) && value.len() == 5 {
-            return Ok(WordBreak);
-            // return Ok(LineBreak);
+        Ok(seq)
+    }
+
+    fn from_morse_slice_in(sequence: &[MorseBit], _: Alphabet) -> Result<Self, MorseError> {
+        Prosign::ALL
+            .into_iter()
+            .find(|p| p.sequence() == sequence)
+            .ok_or_else(|| {
+                MorseError::from(ErrorKind::UnknownMorseSequence).with_elements(sequence)
+            })
+    }
+}
+
+impl MorseConversion for MorseSymbol {
+    fn to_morse_bit_sequence_in<B: Buffer<MorseBit>>(
+        &self,
+        alphabet: Alphabet,
+    ) -> Result<B, MorseError> {
+        match self {
+            MorseSymbol::Char(c) => c.to_morse_bit_sequence_in(alphabet),
+            MorseSymbol::Prosign(p) => p.to_morse_bit_sequence_in(alphabet),
         }
+    }
 
-        Err(MorseError::UnknownBitSequence)
+    // sequences shared between a character and a prosign (AR is '+', BT is '=')
+    // decode as the character, since that is how ITU defines them
+    fn from_morse_slice_in(sequence: &[MorseBit], alphabet: Alphabet) -> Result<Self, MorseError> {
+        alphabet.decode(sequence)
     }
 }
 
@@ -198,51 +322,86 @@ pub const MORSE_TABLE: [&[MorseBit]; 128] = {
     table[b'8' as usize] = &[Dash, Dash, Dash, Dot, Dot];
     table[b'9' as usize] = &[Dash, Dash, Dash, Dash, Dot];
 
+    // punctuation, ITU-R M.1677-1 section 1.1.3
     table[b'.' as usize] = &[Dot, Dash, Dot, Dash, Dot, Dash];
+    table[b',' as usize] = &[Dash, Dash, Dot, Dot, Dash, Dash];
+    table[b':' as usize] = &[Dash, Dash, Dash, Dot, Dot, Dot];
+    table[b'?' as usize] = &[Dot, Dot, Dash, Dash, Dot, Dot];
+    table[b'\'' as usize] = &[Dot, Dash, Dash, Dash, Dash, Dot];
+    table[b'-' as usize] = &[Dash, Dot, Dot, Dot, Dot, Dash];
+    table[b'/' as usize] = &[Dash, Dot, Dot, Dash, Dot];
+    table[b'(' as usize] = &[Dash, Dot, Dash, Dash, Dot];
+    table[b')' as usize] = &[Dash, Dot, Dash, Dash, Dot, Dash];
+    table[b'"' as usize] = &[Dot, Dash, Dot, Dot, Dash, Dot];
+    table[b'=' as usize] = &[Dash, Dot, Dot, Dot, Dash];
+    table[b'+' as usize] = &[Dot, Dash, Dot, Dash, Dot];
+    table[b'@' as usize] = &[Dot, Dash, Dash, Dot, Dash, Dot];
 
     table
 };
 
 use MorseBit::*;
-pub const INVERSE_MORSE_TABLE: &[(&[MorseBit], char)] = &[
-    (&[Dot, Dash], 'A'),
-    (&[Dash, Dot, Dot, Dot], 'B'),
-    (&[Dash, Dot, Dash, Dot], 'C'),
-    (&[Dash, Dot, Dot], 'D'),
-    (&[Dot], 'E'),
-    (&[Dot, Dot, Dash, Dot], 'F'),
-    (&[Dash, Dash, Dot], 'G'),
-    (&[Dot, Dot, Dot, Dot], 'H'),
-    (&[Dot, Dot], 'I'),
-    (&[Dot, Dash, Dash, Dash], 'J'),
-    (&[Dash, Dot, Dash], 'K'),
-    (&[Dot, Dash, Dot, Dot], 'L'),
-    (&[Dash, Dash], 'M'),
-    (&[Dash, Dot], 'N'),
-    (&[Dash, Dash, Dash], 'O'),
-    (&[Dot, Dash, Dash, Dot], 'P'),
-    (&[Dash, Dash, Dot, Dash], 'Q'),
-    (&[Dot, Dash, Dot], 'R'),
-    (&[Dot, Dot, Dot], 'S'),
-    (&[Dash], 'T'),
-    (&[Dot, Dot, Dash], 'U'),
-    (&[Dot, Dot, Dot, Dash], 'V'),
-    (&[Dot, Dash, Dash], 'W'),
-    (&[Dash, Dot, Dot, Dash], 'X'),
-    (&[Dash, Dot, Dash, Dash], 'Y'),
-    (&[Dash, Dash, Dot, Dot], 'Z'),
-    (&[Dash, Dash, Dash, Dash, Dash], '0'),
-    (&[Dot, Dash, Dash, Dash, Dash], '1'),
-    (&[Dot, Dot, Dash, Dash, Dash], '2'),
-    (&[Dot, Dot, Dot, Dash, Dash], '3'),
-    (&[Dot, Dot, Dot, Dot, Dash], '4'),
-    (&[Dot, Dot, Dot, Dot, Dot], '5'),
-    (&[Dash, Dot, Dot, Dot, Dot], '6'),
-    (&[Dash, Dash, Dot, Dot, Dot], '7'),
-    (&[Dash, Dash, Dash, Dot, Dot], '8'),
-    (&[Dash, Dash, Dash, Dash, Dot], '9'),
-    (&[Dot, Dash, Dot, Dash, Dot, Dash], '.'),
-    (&[CharBreak], '\0'),
-    (&[LineBreak], '\n'),
-    (&[WordBreak], ' '),
+pub const INVERSE_MORSE_TABLE: &[(&[MorseBit], MorseSymbol)] = &[
+    (&[Dot, Dash], MorseSymbol::Char('A')),
+    (&[Dash, Dot, Dot, Dot], MorseSymbol::Char('B')),
+    (&[Dash, Dot, Dash, Dot], MorseSymbol::Char('C')),
+    (&[Dash, Dot, Dot], MorseSymbol::Char('D')),
+    (&[Dot], MorseSymbol::Char('E')),
+    (&[Dot, Dot, Dash, Dot], MorseSymbol::Char('F')),
+    (&[Dash, Dash, Dot], MorseSymbol::Char('G')),
+    (&[Dot, Dot, Dot, Dot], MorseSymbol::Char('H')),
+    (&[Dot, Dot], MorseSymbol::Char('I')),
+    (&[Dot, Dash, Dash, Dash], MorseSymbol::Char('J')),
+    (&[Dash, Dot, Dash], MorseSymbol::Char('K')),
+    (&[Dot, Dash, Dot, Dot], MorseSymbol::Char('L')),
+    (&[Dash, Dash], MorseSymbol::Char('M')),
+    (&[Dash, Dot], MorseSymbol::Char('N')),
+    (&[Dash, Dash, Dash], MorseSymbol::Char('O')),
+    (&[Dot, Dash, Dash, Dot], MorseSymbol::Char('P')),
+    (&[Dash, Dash, Dot, Dash], MorseSymbol::Char('Q')),
+    (&[Dot, Dash, Dot], MorseSymbol::Char('R')),
+    (&[Dot, Dot, Dot], MorseSymbol::Char('S')),
+    (&[Dash], MorseSymbol::Char('T')),
+    (&[Dot, Dot, Dash], MorseSymbol::Char('U')),
+    (&[Dot, Dot, Dot, Dash], MorseSymbol::Char('V')),
+    (&[Dot, Dash, Dash], MorseSymbol::Char('W')),
+    (&[Dash, Dot, Dot, Dash], MorseSymbol::Char('X')),
+    (&[Dash, Dot, Dash, Dash], MorseSymbol::Char('Y')),
+    (&[Dash, Dash, Dot, Dot], MorseSymbol::Char('Z')),
+    (&[Dash, Dash, Dash, Dash, Dash], MorseSymbol::Char('0')),
+    (&[Dot, Dash, Dash, Dash, Dash], MorseSymbol::Char('1')),
+    (&[Dot, Dot, Dash, Dash, Dash], MorseSymbol::Char('2')),
+    (&[Dot, Dot, Dot, Dash, Dash], MorseSymbol::Char('3')),
+    (&[Dot, Dot, Dot, Dot, Dash], MorseSymbol::Char('4')),
+    (&[Dot, Dot, Dot, Dot, Dot], MorseSymbol::Char('5')),
+    (&[Dash, Dot, Dot, Dot, Dot], MorseSymbol::Char('6')),
+    (&[Dash, Dash, Dot, Dot, Dot], MorseSymbol::Char('7')),
+    (&[Dash, Dash, Dash, Dot, Dot], MorseSymbol::Char('8')),
+    (&[Dash, Dash, Dash, Dash, Dot], MorseSymbol::Char('9')),
+    (&[Dot, Dash, Dot, Dash, Dot, Dash], MorseSymbol::Char('.')),
+    (&[Dash, Dash, Dot, Dot, Dash, Dash], MorseSymbol::Char(',')),
+    (&[Dash, Dash, Dash, Dot, Dot, Dot], MorseSymbol::Char(':')),
+    (&[Dot, Dot, Dash, Dash, Dot, Dot], MorseSymbol::Char('?')),
+    (&[Dot, Dash, Dash, Dash, Dash, Dot], MorseSymbol::Char('\'')),
+    (&[Dash, Dot, Dot, Dot, Dot, Dash], MorseSymbol::Char('-')),
+    (&[Dash, Dot, Dot, Dash, Dot], MorseSymbol::Char('/')),
+    (&[Dash, Dot, Dash, Dash, Dot], MorseSymbol::Char('(')),
+    (&[Dash, Dot, Dash, Dash, Dot, Dash], MorseSymbol::Char(')')),
+    (&[Dot, Dash, Dot, Dot, Dash, Dot], MorseSymbol::Char('"')),
+    (&[Dash, Dot, Dot, Dot, Dash], MorseSymbol::Char('=')),
+    (&[Dot, Dash, Dot, Dash, Dot], MorseSymbol::Char('+')),
+    (&[Dot, Dash, Dash, Dot, Dash, Dot], MorseSymbol::Char('@')),
+    (&[CharBreak], MorseSymbol::Char('\0')),
+    (&[LineBreak], MorseSymbol::Char('\n')),
+    (&[WordBreak], MorseSymbol::Char(' ')),
+    // prosigns last, so the ones that share a pattern with a character (AR, BT)
+    // are only reachable through `Prosign::from_morse_slice`
+    (Prosign::AR.sequence(), MorseSymbol::Prosign(Prosign::AR)),
+    (Prosign::AS.sequence(), MorseSymbol::Prosign(Prosign::AS)),
+    (Prosign::BT.sequence(), MorseSymbol::Prosign(Prosign::BT)),
+    (Prosign::HH.sequence(), MorseSymbol::Prosign(Prosign::HH)),
+    (Prosign::KA.sequence(), MorseSymbol::Prosign(Prosign::KA)),
+    (Prosign::SK.sequence(), MorseSymbol::Prosign(Prosign::SK)),
+    (Prosign::SN.sequence(), MorseSymbol::Prosign(Prosign::SN)),
+    (Prosign::DO.sequence(), MorseSymbol::Prosign(Prosign::DO)),
 ];
@@ -0,0 +1,521 @@
+use core::iter::{FusedIterator, repeat_n};
+
+use crate::soft::UnaryTrellis;
+use crate::{BIT_SEQUENCE_LEN, Bit, ErrorKind, MorseBit, MorseError, Soft};
+
+/// how morse elements are put on the line as Hi/Lo bits, one bit per time step
+pub trait LineCode {
+    type Bits: Iterator<Item = Bit>;
+    type Decoder: LineDecoder;
+
+    fn encode(&self, m_bit: MorseBit) -> Self::Bits;
+
+    fn decoder(&self) -> Self::Decoder;
+}
+
+/// the receiving half of a [`LineCode`]
+pub trait LineDecoder {
+    /// feeds one line bit, returning an element once its last bit has arrived
+    fn push(&mut self, bit: Bit) -> Result<Option<MorseBit>, MorseError>;
+
+    /// feeds one undecided line bit. codes that can't make use of how sure
+    /// it is just take the [`Soft::hard`] decision
+    fn push_soft(&mut self, soft: Soft) -> Result<Option<MorseBit>, MorseError> {
+        self.push(soft.hard())
+    }
+
+    /// forgets any partial element, called whenever a new frame starts
+    fn reset(&mut self);
+}
+
+/// `hi` Hi bits followed by `lo` Lo bits, which is all any of our codes need
+/// for a single element
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Pulse {
+    hi: u32,
+    lo: u32,
+}
+
+impl Pulse {
+    pub const fn new(hi: u32, lo: u32) -> Self {
+        Self { hi, lo }
+    }
+}
+
+impl Iterator for Pulse {
+    type Item = Bit;
+
+    fn next(&mut self) -> Option<Bit> {
+        if self.hi > 0 {
+            self.hi -= 1;
+            Some(Bit::Hi)
+        } else if self.lo > 0 {
+            self.lo -= 1;
+            Some(Bit::Lo)
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = (self.hi + self.lo) as usize;
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for Pulse {}
+
+impl FusedIterator for Pulse {}
+
+/// our compact machine code: every element is a run of Hi bits closed by a
+/// single Lo, and the length of the run says which element it was
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct UnaryCode {
+    // Hi bits before the closing Lo, indexed by `MorseBit as usize`
+    runs: [u8; 5],
+}
+
+impl UnaryCode {
+    /// dot `L`, char break `HL`, dash `HHL`, line break `HHHL`, word break `HHHHL`
+    pub const COMPACT: Self = Self::new(0, 2, 1, 4, 3);
+
+    /// dot `L`, dash `HL`, char break `HHL`. cheaper whenever dashes outnumber
+    /// chars in the message
+    pub const DASH_FIRST: Self = Self::new(0, 1, 2, 4, 3);
+
+    /// panics if two elements share a run length, or a run would not fit in a
+    /// [`crate::BitSequece`]
+    pub const fn new(dot: u8, dash: u8, char_break: u8, word_break: u8, line_break: u8) -> Self {
+        match Self::checked([dot, dash, char_break, word_break, line_break]) {
+            Ok(code) => code,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    const fn checked(runs: [u8; 5]) -> Result<Self, &'static str> {
+        let mut i = 0;
+        while i < runs.len() {
+            if runs[i] as usize >= BIT_SEQUENCE_LEN {
+                return Err("run does not fit in a bit sequence");
+            }
+            let mut j = i + 1;
+            while j < runs.len() {
+                if runs[i] == runs[j] {
+                    return Err("two elements share a run length");
+                }
+                j += 1;
+            }
+            i += 1;
+        }
+
+        Ok(Self { runs })
+    }
+
+    /// line bits `m_bit` takes, the closing Lo included
+    pub const fn cost(&self, m_bit: MorseBit) -> u32 {
+        self.runs[m_bit as usize] as u32 + 1
+    }
+
+    pub(crate) fn element(&self, run: usize) -> Option<MorseBit> {
+        use MorseBit::*;
+        [Dot, Dash, CharBreak, WordBreak, LineBreak]
+            .into_iter()
+            .find(|m_bit| self.runs[*m_bit as usize] as usize == run)
+    }
+
+    // `element` for every run that fits, so decoding is a lookup
+    fn elements(&self) -> [Option<MorseBit>; BIT_SEQUENCE_LEN] {
+        core::array::from_fn(|run| self.element(run))
+    }
+}
+
+// read back through the same checks as `new`, a code that can't be decoded
+// is refused instead of panicking later
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for UnaryCode {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(serde::Deserialize)]
+        #[serde(rename = "UnaryCode")]
+        struct Fields {
+            runs: [u8; 5],
+        }
+
+        let fields = Fields::deserialize(deserializer)?;
+        Self::checked(fields.runs).map_err(serde::de::Error::custom)
+    }
+}
+
+impl Default for UnaryCode {
+    fn default() -> Self {
+        Self::COMPACT
+    }
+}
+
+impl LineCode for UnaryCode {
+    type Bits = Pulse;
+    type Decoder = UnaryDecoder;
+
+    fn encode(&self, m_bit: MorseBit) -> Pulse {
+        Pulse::new(self.runs[m_bit as usize] as u32, 1)
+    }
+
+    fn decoder(&self) -> UnaryDecoder {
+        let elements = self.elements();
+        UnaryDecoder {
+            elements,
+            hi: 0,
+            trellis: UnaryTrellis::new(elements),
+        }
+    }
+}
+
+/// counts Hi bits up to the closing Lo and looks the count up, so all it keeps
+/// of an element is its length and nothing is copied unless there's an error
+/// to report. soft bits go through a viterbi search instead, which rides out
+/// bits read wrong but hands out elements a few bits late, see
+/// [`LineDecoder::push_soft`]
+#[derive(Clone, Debug)]
+pub struct UnaryDecoder {
+    // what each run of Hi bits stands for
+    elements: [Option<MorseBit>; BIT_SEQUENCE_LEN],
+    // Hi bits of the element so far
+    hi: u8,
+    trellis: UnaryTrellis,
+}
+
+impl LineDecoder for UnaryDecoder {
+    // runs for every bit, so let callers in other crates inline it
+    #[inline]
+    fn push(&mut self, bit: Bit) -> Result<Option<MorseBit>, MorseError> {
+        let run = self.hi as usize;
+        if run == BIT_SEQUENCE_LEN {
+            return Err(run_error(ErrorKind::FullBuffer, run, None));
+        }
+        if bit == Bit::Hi {
+            self.hi += 1;
+            return Ok(None);
+        }
+
+        self.hi = 0;
+        match self.elements[run] {
+            Some(m_bit) => Ok(Some(m_bit)),
+            None => Err(run_error(ErrorKind::UnknownBitSequence, run, Some(Bit::Lo))),
+        }
+    }
+
+    fn push_soft(&mut self, soft: Soft) -> Result<Option<MorseBit>, MorseError> {
+        self.trellis.push(soft)
+    }
+
+    fn reset(&mut self) {
+        self.hi = 0;
+        self.trellis.reset();
+    }
+}
+
+// a run of Hi bits, and the bit that closed it if any. out of line, so that
+// building it doesn't weigh on the bits that are fine
+#[cold]
+fn run_error(kind: ErrorKind, run: usize, end: Option<Bit>) -> MorseError {
+    MorseError::from(kind).with_bits(repeat_n(Bit::Hi, run).chain(end))
+}
+
+/// classic on/off keying with ITU-R M.1677 timing, one bit per dot length:
+/// a dot is 1 unit on, a dash 3 on, and elements are separated by 1 unit
+/// off, letters by 3 and words by 7. sent at a human speed this is real morse
+/// that an operator or fldigi can copy straight off the LED.
+///
+/// there is no standard end of message gap, so a frame is closed by staying
+/// off for a letter gap past the word gap.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ItuCode {
+    char_wpm: u32,
+    letter_gap: u32,
+    word_gap: u32,
+}
+
+impl ItuCode {
+    const DOT: u32 = 1;
+    const DASH: u32 = 3;
+    const ELEMENT_GAP: u32 = 1;
+
+    /// standard spacing at `wpm` words per minute, measured with PARIS.
+    /// panics if `wpm` is 0
+    pub const fn new(wpm: u32) -> Self {
+        Self {
+            char_wpm: wpm,
+            letter_gap: 3,
+            word_gap: 7,
+        }
+        .checked_or_panic()
+    }
+
+    /// characters keyed at `char_wpm` but with the letter and word gaps
+    /// stretched until the overall speed drops to `wpm`, per the ARRL
+    /// formula. asking for an overall speed at or above the character speed
+    /// just gives standard spacing. panics if either speed is 0
+    pub const fn farnsworth(char_wpm: u32, wpm: u32) -> Self {
+        assert!(wpm > 0, "morse can't be keyed at 0 wpm");
+        if wpm >= char_wpm {
+            return Self::new(char_wpm);
+        }
+
+        // the ARRL total delay is ta = (60c - 37.2s) / (sc) seconds, split 3:7
+        // over letter and word gaps out of 19 gap units. one dot is 1.2 / c
+        // seconds, which leaves (60c - 37.2s) / (22.8s) dots per gap unit
+        let (c, s) = (char_wpm as u64, wpm as u64);
+        let num = 600 * c - 372 * s;
+        let den = 228 * s;
+
+        Self {
+            char_wpm,
+            letter_gap: ((3 * num + den / 2) / den) as u32,
+            word_gap: ((7 * num + den / 2) / den) as u32,
+        }
+        .checked_or_panic()
+    }
+
+    // every gap has to hold the element gap before it, and a word gap the
+    // two letter gaps around its break. the decoder adds gaps up, so they're
+    // kept far from overflowing
+    const fn checked(self) -> Result<Self, &'static str> {
+        if self.char_wpm == 0 {
+            return Err("morse can't be keyed at 0 wpm");
+        }
+        if self.word_gap > u16::MAX as u32 {
+            return Err("gaps too long to measure");
+        }
+        if self.letter_gap < Self::ELEMENT_GAP
+            || self.word_gap as u64 + 1 < 2 * self.letter_gap as u64
+        {
+            return Err("gaps too short for the elements they separate");
+        }
+        Ok(self)
+    }
+
+    const fn checked_or_panic(self) -> Self {
+        match self.checked() {
+            Ok(code) => code,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// length of a dot, which is also how long each line bit is held
+    pub const fn unit_micros(&self) -> u64 {
+        1_200_000 / self.char_wpm as u64
+    }
+
+    pub const fn char_wpm(&self) -> u32 {
+        self.char_wpm
+    }
+
+    pub const fn letter_gap(&self) -> u32 {
+        self.letter_gap
+    }
+
+    pub const fn word_gap(&self) -> u32 {
+        self.word_gap
+    }
+
+    pub const fn end_gap(&self) -> u32 {
+        self.word_gap + self.letter_gap
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for ItuCode {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(serde::Deserialize)]
+        #[serde(rename = "ItuCode")]
+        struct Fields {
+            char_wpm: u32,
+            letter_gap: u32,
+            word_gap: u32,
+        }
+
+        let fields = Fields::deserialize(deserializer)?;
+        Self {
+            char_wpm: fields.char_wpm,
+            letter_gap: fields.letter_gap,
+            word_gap: fields.word_gap,
+        }
+        .checked()
+        .map_err(serde::de::Error::custom)
+    }
+}
+
+impl Default for ItuCode {
+    fn default() -> Self {
+        Self::new(20)
+    }
+}
+
+impl LineCode for ItuCode {
+    type Bits = Pulse;
+    type Decoder = ItuDecoder;
+
+    // every element carries its trailing element gap, so the breaks only add
+    // whatever is missing on top of that. a word break sits between two char
+    // breaks, which is why it is short.
+    fn encode(&self, m_bit: MorseBit) -> Pulse {
+        let char_extra = self.letter_gap - Self::ELEMENT_GAP;
+        match m_bit {
+            MorseBit::Dot => Pulse::new(Self::DOT, Self::ELEMENT_GAP),
+            MorseBit::Dash => Pulse::new(Self::DASH, Self::ELEMENT_GAP),
+            MorseBit::CharBreak => Pulse::new(0, char_extra),
+            MorseBit::WordBreak => {
+                Pulse::new(0, self.word_gap - 2 * char_extra - Self::ELEMENT_GAP)
+            }
+            MorseBit::LineBreak => Pulse::new(0, self.end_gap() - Self::ELEMENT_GAP),
+        }
+    }
+
+    fn decoder(&self) -> ItuDecoder {
+        ItuDecoder {
+            code: *self,
+            level: Bit::Lo,
+            run: 0,
+            ended: false,
+        }
+    }
+}
+
+/// measures every on and off run and sorts it against the midpoints between
+/// the nominal lengths, so a run can be a unit short or long and still land
+/// on the right element
+#[derive(Clone, Debug)]
+pub struct ItuDecoder {
+    code: ItuCode,
+    level: Bit,
+    run: u32,
+    // the end gap was already reported for the current off run
+    ended: bool,
+}
+
+impl LineDecoder for ItuDecoder {
+    fn push(&mut self, bit: Bit) -> Result<Option<MorseBit>, MorseError> {
+        let code = &self.code;
+
+        if bit == self.level {
+            self.run = self.run.saturating_add(1);
+
+            if bit == Bit::Lo && !self.ended && self.run * 2 >= code.word_gap + code.end_gap() {
+                self.ended = true;
+                return Ok(Some(MorseBit::LineBreak));
+            }
+            if bit == Bit::Hi && self.run > 2 * ItuCode::DASH {
+                let run = repeat_n(Bit::Hi, self.run as usize);
+                return Err(MorseError::from(ErrorKind::UnknownBitSequence).with_bits(run));
+            }
+            return Ok(None);
+        }
+
+        let run = self.run;
+        self.level = bit;
+        self.run = 1;
+
+        let m_bit = match bit {
+            // an on run just ended
+            Bit::Lo if run * 2 < ItuCode::DOT + ItuCode::DASH => Some(MorseBit::Dot),
+            Bit::Lo => Some(MorseBit::Dash),
+            // an off run just ended
+            Bit::Hi if core::mem::take(&mut self.ended) => None,
+            Bit::Hi if run * 2 < ItuCode::ELEMENT_GAP + code.letter_gap => None,
+            Bit::Hi if run * 2 < code.letter_gap + code.word_gap => Some(MorseBit::CharBreak),
+            Bit::Hi => Some(MorseBit::WordBreak),
+        };
+
+        Ok(m_bit)
+    }
+
+    fn reset(&mut self) {
+        self.level = Bit::Lo;
+        self.run = 0;
+        self.ended = false;
+    }
+}
+
+/// either of the line codes above, picked at runtime, for when the link is
+/// set up from a [`crate::LinkConfig`] instead of at compile time
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AnyLineCode {
+    Unary(UnaryCode),
+    Itu(ItuCode),
+}
+
+impl Default for AnyLineCode {
+    fn default() -> Self {
+        AnyLineCode::Unary(UnaryCode::default())
+    }
+}
+
+impl From<UnaryCode> for AnyLineCode {
+    fn from(code: UnaryCode) -> Self {
+        AnyLineCode::Unary(code)
+    }
+}
+
+impl From<ItuCode> for AnyLineCode {
+    fn from(code: ItuCode) -> Self {
+        AnyLineCode::Itu(code)
+    }
+}
+
+impl LineCode for AnyLineCode {
+    type Bits = Pulse;
+    type Decoder = AnyLineDecoder;
+
+    fn encode(&self, m_bit: MorseBit) -> Pulse {
+        match self {
+            AnyLineCode::Unary(code) => code.encode(m_bit),
+            AnyLineCode::Itu(code) => code.encode(m_bit),
+        }
+    }
+
+    fn decoder(&self) -> AnyLineDecoder {
+        match self {
+            AnyLineCode::Unary(code) => AnyLineDecoder::Unary(code.decoder()),
+            AnyLineCode::Itu(code) => AnyLineDecoder::Itu(code.decoder()),
+        }
+    }
+}
+
+// the unary trellis dwarfs the itu decoder, but there's nowhere to box it and
+// only ever one decoder per receiver
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone, Debug)]
+pub enum AnyLineDecoder {
+    Unary(UnaryDecoder),
+    Itu(ItuDecoder),
+}
+
+impl LineDecoder for AnyLineDecoder {
+    fn push(&mut self, bit: Bit) -> Result<Option<MorseBit>, MorseError> {
+        match self {
+            AnyLineDecoder::Unary(decoder) => decoder.push(bit),
+            AnyLineDecoder::Itu(decoder) => decoder.push(bit),
+        }
+    }
+
+    fn push_soft(&mut self, soft: Soft) -> Result<Option<MorseBit>, MorseError> {
+        match self {
+            AnyLineDecoder::Unary(decoder) => decoder.push_soft(soft),
+            AnyLineDecoder::Itu(decoder) => decoder.push_soft(soft),
+        }
+    }
+
+    fn reset(&mut self) {
+        match self {
+            AnyLineDecoder::Unary(decoder) => decoder.reset(),
+            AnyLineDecoder::Itu(decoder) => decoder.reset(),
+        }
+    }
+}
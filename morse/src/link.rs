@@ -0,0 +1,314 @@
+use core::iter::{Chain, FusedIterator, RepeatN};
+
+use crate::{
+    Alphabet, AnyLineCode, BIT_SEQUENCE_LEN, Biphase, BiphaseDecoder, BiphaseEncoder, Bit,
+    Checksum, Fec, FrameEncoder, ItuCode, MessageType, MorseEncoder, MorseError, PackedIter,
+    Parity, Preamble, Sampler, Soft, UnaryCode,
+};
+
+/// Lo bits sent after every message. a soft receiver decides a few bits late,
+/// and a biphase line only clocks while there are bits on it
+pub const TAIL_BITS: usize = BIT_SEQUENCE_LEN;
+
+/// what the link carries after the preamble
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Framing {
+    /// text keyed through the line code, see [`MorseEncoder`]
+    #[default]
+    Morse,
+    /// versioned binary frames, see [`FrameEncoder`]. the line code is unused
+    Frames,
+}
+
+/// everything tx and rx have to agree on, in one place, so a link can be set
+/// up at runtime instead of by editing consts on both ends.
+///
+/// tx only looks at the first half, rx needs all of it. start from a preset
+/// and change what differs:
+///
+/// ```
+/// use morse::LinkConfig;
+///
+/// let config = LinkConfig {
+///     threshold: 180,
+///     ..LinkConfig::COMPACT
+/// };
+/// assert_eq!(config.samples_per_step(), 1);
+/// ```
+#[derive(Copy, Clone, PartialEq, Debug, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LinkConfig {
+    /// how long tx holds every line bit
+    pub time_step_micros: u64,
+    pub preamble: Preamble,
+    pub line_code: AnyLineCode,
+    /// sends every line bit, preamble included, as two self clocking halves
+    pub biphase: Option<Biphase>,
+    pub framing: Framing,
+    /// what morse text is keyed in, wabun can also be shifted into from any
+    /// of the others
+    pub alphabet: Alphabet,
+    /// trailer on morse text, frames always carry [`crate::FRAME_CHECKSUM`]
+    pub checksum: Checksum,
+    /// error correction for frames, morse text doesn't get any
+    pub fec: Fec,
+    /// compresses frame payloads whenever that makes them shorter. only tx
+    /// looks at it, the frame header tells rx
+    pub compress: bool,
+    /// lowest adc reading rx takes as Hi
+    pub threshold: u16,
+    /// how fast rx reads the adc
+    pub sample_hertz: u64,
+}
+
+impl LinkConfig {
+    /// the compact machine code as fast as the boards go, read about once per
+    /// bit. on the line it's exactly what tx sent before there were presets
+    pub const COMPACT: Self = Self {
+        time_step_micros: 11,
+        preamble: Preamble::LEGACY,
+        line_code: AnyLineCode::Unary(UnaryCode::COMPACT),
+        biphase: None,
+        framing: Framing::Morse,
+        alphabet: Alphabet::Latin,
+        checksum: Checksum::None,
+        fec: Fec::None,
+        compress: false,
+        threshold: 210,
+        sample_hertz: 83322,
+    };
+
+    /// [`LinkConfig::COMPACT`] for a noisy line. the line starts with
+    /// [`Preamble::BARKER_13`], text gets a CRC-16 trailer and frames get
+    /// reed-solomon parity. boards on the compact preset can't hear it, so
+    /// tx and rx both need it
+    pub const HARDENED: Self = Self {
+        preamble: Preamble::BARKER_13,
+        checksum: Checksum::Crc16,
+        fec: Fec::ReedSolomon {
+            parity: Parity::fixed(8),
+        },
+        ..Self::COMPACT
+    };
+
+    /// real morse at 20 wpm, slow enough for a human to copy off the LED
+    pub const ITU: Self = Self::itu(ItuCode::new(20));
+
+    /// the compact code on a manchester line. it needs a few samples per half
+    /// bit to find the edges, 96us gives rx about 4
+    pub const MANCHESTER: Self = Self {
+        time_step_micros: 96,
+        biphase: Some(Biphase::Manchester),
+        ..Self::COMPACT
+    };
+
+    /// binary frames at the compact speed
+    pub const FRAMES: Self = Self {
+        framing: Framing::Frames,
+        ..Self::COMPACT
+    };
+
+    /// keys `code` for humans, one line bit per dot
+    pub const fn itu(code: ItuCode) -> Self {
+        Self {
+            time_step_micros: code.unit_micros(),
+            line_code: AnyLineCode::Itu(code),
+            ..Self::COMPACT
+        }
+    }
+
+    /// how long tx holds the LED at one level, half a step on a biphase line
+    pub const fn level_micros(&self) -> u64 {
+        match self.biphase {
+            Some(_) => self.time_step_micros / 2,
+            None => self.time_step_micros,
+        }
+    }
+
+    /// readings rx gets per level at `sample_hertz`, never less than 1
+    pub const fn samples_per_step(&self) -> u32 {
+        let samples = (self.sample_hertz * self.level_micros() + 500_000) / 1_000_000;
+        if samples == 0 { 1 } else { samples as u32 }
+    }
+
+    /// the same link, with rx sampling fast enough to get `samples` readings
+    /// per level. this is how rx settings are derived from the tx ones
+    pub const fn with_oversampling(self, samples: u32) -> Self {
+        Self {
+            sample_hertz: samples as u64 * 1_000_000 / self.level_micros(),
+            ..self
+        }
+    }
+
+    /// thresholds one adc reading
+    pub const fn sample(&self, reading: u16) -> Bit {
+        if reading < self.threshold {
+            Bit::Lo
+        } else {
+            Bit::Hi
+        }
+    }
+
+    /// one adc reading as a soft bit, how far it is above the threshold
+    pub const fn soft_sample(&self, reading: u16) -> Soft {
+        let above = reading as i32 - self.threshold as i32;
+        if above > i16::MAX as i32 {
+            Soft(i16::MAX)
+        } else if above < i16::MIN as i32 {
+            Soft(i16::MIN)
+        } else {
+            Soft(above as i16)
+        }
+    }
+
+    /// recovers line bits from rx's thresholded readings
+    pub const fn clock(&self) -> BitClock {
+        match self.biphase {
+            Some(mode) => BitClock::Biphase(mode.decoder(self.samples_per_step())),
+            None => BitClock::Sampled(Sampler::new(self.samples_per_step())),
+        }
+    }
+
+    /// everything tx puts on the line for `message`, preamble first and
+    /// [`TAIL_BITS`] last. the whole
+    /// message is checked up front, so nothing fails halfway through sending.
+    /// `sequence` numbers the frame when sending [`Framing::Frames`]
+    pub fn transmission<'a>(
+        &self,
+        message: &'a str,
+        sequence: u16,
+    ) -> Result<Transmission<'a>, MorseError> {
+        let payload = match self.framing {
+            Framing::Morse => {
+                let encoder = || MorseEncoder::with_config(message.chars(), self);
+                encoder().try_for_each(|bit| bit.map(drop))?;
+                Payload::Morse(encoder())
+            }
+            Framing::Frames => {
+                let encoder = match self.compress {
+                    true => FrameEncoder::compressed,
+                    false => FrameEncoder::new,
+                };
+                Payload::Frame(
+                    encoder(MessageType::Text, sequence, message.as_bytes())?.with_fec(self.fec),
+                )
+            }
+        };
+
+        Ok(self.line(payload))
+    }
+
+    /// [`LinkConfig::transmission`] as a frame sealed with `key`, whatever
+    /// the framing. the message is compressed first if the config says so,
+    /// then encrypted into `buffer`, which has to fit it plus
+    /// [`crate::SESSION_LEN`] and [`crate::TAG_LEN`]. see
+    /// [`FrameEncoder::sealed`] for when `session` has to change
+    #[cfg(feature = "aead")]
+    pub fn sealed_transmission<'a>(
+        &self,
+        message: &str,
+        sequence: u16,
+        session: u64,
+        key: &crate::FrameKey,
+        buffer: &'a mut [u8],
+    ) -> Result<Transmission<'a>, MorseError> {
+        let mut header = crate::FrameHeader::new(MessageType::Text, sequence, 0);
+        if self.compress {
+            header.flags |= crate::FLAG_COMPRESSED;
+        }
+        let frame = FrameEncoder::sealed(header, session, message.as_bytes(), key, buffer)?;
+        Ok(self.line(Payload::Frame(frame.with_fec(self.fec))))
+    }
+
+    fn line<'a>(&self, payload: Payload<'a>) -> Transmission<'a> {
+        let bits = self
+            .preamble
+            .packed()
+            .into_iter()
+            .chain(payload)
+            .chain(core::iter::repeat_n(Bit::Lo, TAIL_BITS));
+        Transmission(match self.biphase {
+            Some(mode) => Line::Biphase(mode.encode(bits)),
+            None => Line::Plain(bits),
+        })
+    }
+}
+
+impl Default for LinkConfig {
+    fn default() -> Self {
+        Self::COMPACT
+    }
+}
+
+enum Payload<'a> {
+    // checked before it got here, so it never yields an error
+    Morse(MorseEncoder<core::str::Chars<'a>, AnyLineCode>),
+    Frame(FrameEncoder<'a>),
+}
+
+impl Iterator for Payload<'_> {
+    type Item = Bit;
+
+    fn next(&mut self) -> Option<Bit> {
+        match self {
+            Payload::Morse(bits) => bits.next()?.ok(),
+            Payload::Frame(bits) => bits.next(),
+        }
+    }
+}
+
+type LineBits<'a> = Chain<Chain<PackedIter<1>, Payload<'a>>, RepeatN<Bit>>;
+
+enum Line<'a> {
+    Plain(LineBits<'a>),
+    Biphase(BiphaseEncoder<LineBits<'a>>),
+}
+
+/// the levels tx puts on the LED for one message, each held for
+/// [`LinkConfig::level_micros`]
+pub struct Transmission<'a>(Line<'a>);
+
+impl Iterator for Transmission<'_> {
+    type Item = Bit;
+
+    fn next(&mut self) -> Option<Bit> {
+        match &mut self.0 {
+            Line::Plain(bits) => bits.next(),
+            Line::Biphase(halves) => halves.next(),
+        }
+    }
+}
+
+impl FusedIterator for Transmission<'_> {}
+
+/// rx's bit clock, picked by [`LinkConfig::clock`]
+#[derive(Clone, Debug)]
+pub enum BitClock {
+    /// resamples a plain line down to one bit per step
+    Sampled(Sampler),
+    /// follows the transmitter's clock on a biphase line
+    Biphase(BiphaseDecoder),
+}
+
+impl BitClock {
+    /// feeds one thresholded reading, returning a bit whenever one is due
+    pub fn push(&mut self, sample: Bit) -> Result<Option<Bit>, MorseError> {
+        match self {
+            BitClock::Sampled(sampler) => Ok(sampler.push(sample)),
+            BitClock::Biphase(decoder) => decoder.push(sample),
+        }
+    }
+
+    /// [`BitClock::push`] for rx's soft readings. a plain line hands out the
+    /// reading the bit was taken on, a biphase one only knows the bit it
+    /// decided on
+    pub fn push_soft(&mut self, sample: Soft) -> Result<Option<Soft>, MorseError> {
+        match self {
+            BitClock::Sampled(sampler) => Ok(sampler.push(sample.hard()).map(|_| sample)),
+            BitClock::Biphase(decoder) => Ok(decoder.push(sample.hard())?.map(Soft::certain)),
+        }
+    }
+}
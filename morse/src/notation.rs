@@ -0,0 +1,126 @@
+use core::fmt;
+
+use crate::{Bit, Buffer, ErrorKind, MorseBit, MorseError};
+
+/// `.` and `-` for the elements, a space between chars, `/` for a word break
+/// and a newline for the line break, so `A B` encodes to `.- / -...` plus the
+/// newline
+impl fmt::Display for MorseBit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            MorseBit::Dot => ".",
+            MorseBit::Dash => "-",
+            MorseBit::CharBreak => " ",
+            MorseBit::WordBreak => "/",
+            MorseBit::LineBreak => "\n",
+        })
+    }
+}
+
+/// `H` or `L`
+impl fmt::Display for Bit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Bit::Hi => "H",
+            Bit::Lo => "L",
+        })
+    }
+}
+
+/// prints a sequence in the short notation instead of one variant per line,
+/// `.- / -...` for elements and `HHL` for line bits. [`parse_morse`] and
+/// [`parse_bits`] read it back.
+///
+/// ```
+/// use morse::{Notation, MorseBit::*};
+///
+/// assert_eq!(Notation(&[Dot, Dash, CharBreak, Dash]).to_string(), ".- -");
+/// ```
+#[derive(Copy, Clone, PartialEq, Debug, Hash)]
+pub struct Notation<'a, T>(pub &'a [T]);
+
+impl<T: fmt::Display> fmt::Display for Notation<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.iter().try_for_each(|item| item.fmt(f))
+    }
+}
+
+/// reads dot-dash notation into elements. chars are separated by whitespace,
+/// and words by `/` or a gap of two or more spaces. a newline is a line
+/// break. `·` is taken as a dot and `_` or `−` as a dash.
+///
+/// the breaks come out the way [`crate::MorseEncoder`] sends them, with
+/// every char slot, word breaks included, delimited by a char break. they're
+/// collected into any [`Buffer`], a [`crate::MorseBitSequence`] or a `Vec`.
+pub fn parse_morse<B: Buffer<MorseBit>>(text: &str) -> Result<B, MorseError> {
+    #[derive(PartialEq)]
+    enum Last {
+        // start of the text or right after a line break
+        Nothing,
+        Element,
+        Word,
+    }
+
+    let mut seq = B::NEW;
+    let mut push = |m_bit| seq.push(m_bit);
+    let mut last = Last::Nothing;
+    let mut spaces = 0;
+
+    for c in text.chars() {
+        let slot = match c {
+            '.' | '·' => Some(MorseBit::Dot),
+            '-' | '_' | '−' => Some(MorseBit::Dash),
+            '/' => Some(MorseBit::WordBreak),
+            '\n' => {
+                push(MorseBit::LineBreak)?;
+                last = Last::Nothing;
+                spaces = 0;
+                None
+            }
+            c if c.is_whitespace() => {
+                spaces += 1;
+                None
+            }
+            _ => return Err(ErrorKind::UnknownMorseSequence.into()),
+        };
+        let Some(m_bit) = slot else {
+            continue;
+        };
+
+        let continues_char = last == Last::Element && spaces == 0 && m_bit != MorseBit::WordBreak;
+        if last != Last::Nothing && !continues_char {
+            if last == Last::Element && spaces >= 2 && m_bit != MorseBit::WordBreak {
+                push(MorseBit::CharBreak)?;
+                push(MorseBit::WordBreak)?;
+            }
+            push(MorseBit::CharBreak)?;
+        }
+
+        push(m_bit)?;
+        last = match m_bit {
+            MorseBit::WordBreak => Last::Word,
+            _ => Last::Element,
+        };
+        spaces = 0;
+    }
+
+    Ok(seq)
+}
+
+/// reads line bits written as `H` and `L`, either case. whitespace and `_` are
+/// skipped, so long runs can be grouped for reading. like [`parse_morse`] they
+/// go into any [`Buffer`]
+pub fn parse_bits<B: Buffer<Bit>>(text: &str) -> Result<B, MorseError> {
+    let mut bits = B::NEW;
+    for c in text.chars() {
+        let bit = match c {
+            'H' | 'h' => Bit::Hi,
+            'L' | 'l' => Bit::Lo,
+            '_' => continue,
+            c if c.is_whitespace() => continue,
+            _ => return Err(ErrorKind::UnknownBitSequence.into()),
+        };
+        bits.push(bit)?;
+    }
+    Ok(bits)
+}
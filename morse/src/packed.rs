@@ -0,0 +1,264 @@
+use core::fmt;
+use core::iter::FusedIterator;
+
+use crate::{Bit, ErrorKind, MorseError};
+
+const WORD_BITS: usize = u32::BITS as usize;
+
+/// line bits packed 32 to a `u32`, in `W` words, instead of a byte for every
+/// [`Bit`]. the first bit sits in the highest place of the first word, so a
+/// stretch of them reads as an integer the way a shift register would hold
+/// it, and comparing two stretches is an xor and a popcount.
+///
+/// ```
+/// use morse::{BitSequece, PackedBits, parse_bits};
+///
+/// let bits = PackedBits::<1>::from_slice(&parse_bits::<BitSequece<5>>("HHLHL").unwrap());
+/// assert_eq!(bits.word(0, 4), Some(0b1101));
+/// assert_eq!(bits.word(2, 3), Some(0b010));
+/// ```
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+pub struct PackedBits<const W: usize> {
+    // everything past `len` stays 0, so the derives can look at whole words
+    words: [u32; W],
+    len: usize,
+}
+
+impl<const W: usize> PackedBits<W> {
+    pub const CAPACITY: usize = W * WORD_BITS;
+
+    pub const fn new() -> Self {
+        Self {
+            words: [0; W],
+            len: 0,
+        }
+    }
+
+    /// panics if `bits` doesn't fit, for packing patterns in consts
+    pub const fn from_slice(bits: &[Bit]) -> Self {
+        assert!(bits.len() <= Self::CAPACITY, "too many bits to pack");
+        let mut packed = Self::new();
+        while packed.len < bits.len() {
+            if matches!(bits[packed.len], Bit::Hi) {
+                packed.words[packed.len / WORD_BITS] |= mask(packed.len);
+            }
+            packed.len += 1;
+        }
+        packed
+    }
+
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub const fn is_full(&self) -> bool {
+        self.len == Self::CAPACITY
+    }
+
+    pub fn push(&mut self, bit: Bit) -> Result<(), MorseError> {
+        if self.is_full() {
+            return Err(ErrorKind::FullBuffer.into());
+        }
+        if bit == Bit::Hi {
+            self.words[self.len / WORD_BITS] |= mask(self.len);
+        }
+        self.len += 1;
+        Ok(())
+    }
+
+    // the first bits, as many as fit in a word, the way `word` reads them
+    pub(crate) const fn head(&self) -> u32 {
+        match self.len {
+            0 => 0,
+            len if len < WORD_BITS => self.words[0] >> (WORD_BITS - len),
+            _ => self.words[0],
+        }
+    }
+
+    pub fn get(&self, i: usize) -> Option<Bit> {
+        if i >= self.len {
+            return None;
+        }
+        Some(if self.words[i / WORD_BITS] & mask(i) != 0 {
+            Bit::Hi
+        } else {
+            Bit::Lo
+        })
+    }
+
+    pub fn truncate(&mut self, len: usize) {
+        if len >= self.len {
+            return;
+        }
+        let word = len / WORD_BITS;
+        self.words[word] &= !(u32::MAX >> (len % WORD_BITS));
+        self.words[word + 1..].fill(0);
+        self.len = len;
+    }
+
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    pub fn iter(&self) -> PackedIter<W> {
+        PackedIter {
+            bits: *self,
+            next: 0,
+        }
+    }
+
+    /// the `len` bits from `at` on as an integer, the first in the highest
+    /// place. `None` if they run past the end or there are more than 32
+    pub fn word(&self, at: usize, len: usize) -> Option<u32> {
+        if len > WORD_BITS || at.checked_add(len)? > self.len {
+            return None;
+        }
+        if len == 0 {
+            return Some(0);
+        }
+
+        // two words side by side, so a stretch across the boundary reads the
+        // same as one inside a word
+        let i = at / WORD_BITS;
+        let next = self.words.get(i + 1).copied().unwrap_or(0);
+        let pair = (self.words[i] as u64) << WORD_BITS | next as u64;
+        Some((pair << (at % WORD_BITS) >> (2 * WORD_BITS - len)) as u32)
+    }
+}
+
+// the bit for index `i` within its word
+const fn mask(i: usize) -> u32 {
+    1 << (WORD_BITS - 1 - i % WORD_BITS)
+}
+
+impl<const W: usize> Default for PackedBits<W> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const W: usize> PartialEq<[Bit]> for PackedBits<W> {
+    fn eq(&self, other: &[Bit]) -> bool {
+        self.len == other.len() && self.iter().eq(other.iter().copied())
+    }
+}
+
+impl<const W: usize> TryFrom<&[Bit]> for PackedBits<W> {
+    type Error = MorseError;
+
+    fn try_from(bits: &[Bit]) -> Result<Self, MorseError> {
+        let mut packed = Self::new();
+        bits.iter().try_for_each(|bit| packed.push(*bit))?;
+        Ok(packed)
+    }
+}
+
+// in the short notation, the words mean little to a reader
+impl<const W: usize> fmt::Debug for PackedBits<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("PackedBits(")?;
+        self.iter().try_for_each(|bit| fmt::Display::fmt(&bit, f))?;
+        f.write_str(")")
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl<const W: usize> defmt::Format for PackedBits<W> {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "PackedBits(");
+        for bit in self.iter() {
+            match bit {
+                Bit::Hi => defmt::write!(f, "H"),
+                Bit::Lo => defmt::write!(f, "L"),
+            }
+        }
+        defmt::write!(f, ")");
+    }
+}
+
+// a list of bits like every other sequence, not the words
+#[cfg(feature = "serde")]
+impl<const W: usize> serde::Serialize for PackedBits<W> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, const W: usize> serde::Deserialize<'de> for PackedBits<W> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Bits<const W: usize>;
+
+        impl<'de, const W: usize> serde::de::Visitor<'de> for Bits<W> {
+            type Value = PackedBits<W>;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "at most {} bits", PackedBits::<W>::CAPACITY)
+            }
+
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(
+                self,
+                mut seq: A,
+            ) -> Result<Self::Value, A::Error> {
+                let mut bits = PackedBits::new();
+                while let Some(bit) = seq.next_element()? {
+                    bits.push(bit).map_err(serde::de::Error::custom)?;
+                }
+                Ok(bits)
+            }
+        }
+
+        deserializer.deserialize_seq(Bits)
+    }
+}
+
+/// the bits of a [`PackedBits`], first to last
+#[derive(Clone, Debug)]
+pub struct PackedIter<const W: usize> {
+    bits: PackedBits<W>,
+    next: usize,
+}
+
+impl<const W: usize> Iterator for PackedIter<W> {
+    type Item = Bit;
+
+    fn next(&mut self) -> Option<Bit> {
+        let bit = self.bits.get(self.next)?;
+        self.next += 1;
+        Some(bit)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.bits.len - self.next;
+        (len, Some(len))
+    }
+}
+
+impl<const W: usize> ExactSizeIterator for PackedIter<W> {}
+
+impl<const W: usize> FusedIterator for PackedIter<W> {}
+
+impl<const W: usize> IntoIterator for PackedBits<W> {
+    type Item = Bit;
+    type IntoIter = PackedIter<W>;
+
+    fn into_iter(self) -> PackedIter<W> {
+        PackedIter {
+            bits: self,
+            next: 0,
+        }
+    }
+}
+
+impl<const W: usize> IntoIterator for &PackedBits<W> {
+    type Item = Bit;
+    type IntoIter = PackedIter<W>;
+
+    fn into_iter(self) -> PackedIter<W> {
+        self.iter()
+    }
+}
@@ -0,0 +1,248 @@
+use crate::{Bit, PackedBits, START_SEQUENCE};
+
+/// longest preamble a [`StartDetector`] can look for, as many bits as its
+/// window register holds
+pub const MAX_PREAMBLE_LEN: usize = u32::BITS as usize;
+
+/// the bits that announce a frame, and how many of them may arrive flipped
+/// before a window stops counting as a match.
+///
+/// sequences with low autocorrelation sidelobes, like barker codes and
+/// m-sequences, only line up with themselves when perfectly aligned, so a few
+/// errors can be forgiven without the match landing a bit early or late.
+#[derive(Copy, Clone, PartialEq, Debug, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Preamble {
+    bits: &'static [Bit],
+    packed: PackedBits<1>,
+    max_errors: usize,
+}
+
+impl Preamble {
+    /// [`START_SEQUENCE`], exactly. what every receiver used before
+    pub const LEGACY: Self = Self::new(&START_SEQUENCE, 0);
+
+    /// the length 13 barker code, whose sidelobes never go above 1, so it
+    /// survives two flipped bits
+    pub const BARKER_13: Self = Self::new(
+        &[
+            Bit::Hi,
+            Bit::Hi,
+            Bit::Hi,
+            Bit::Hi,
+            Bit::Hi,
+            Bit::Lo,
+            Bit::Lo,
+            Bit::Hi,
+            Bit::Hi,
+            Bit::Lo,
+            Bit::Hi,
+            Bit::Lo,
+            Bit::Hi,
+        ],
+        2,
+    );
+
+    /// the length 15 m-sequence from `x^4 + x^3 + 1`, surviving three flipped
+    /// bits
+    pub const M_SEQUENCE_15: Self = Self::new(
+        &[
+            Bit::Hi,
+            Bit::Hi,
+            Bit::Hi,
+            Bit::Hi,
+            Bit::Lo,
+            Bit::Hi,
+            Bit::Lo,
+            Bit::Hi,
+            Bit::Hi,
+            Bit::Lo,
+            Bit::Lo,
+            Bit::Hi,
+            Bit::Lo,
+            Bit::Lo,
+            Bit::Lo,
+        ],
+        3,
+    );
+
+    /// panics if `bits` is empty or longer than [`MAX_PREAMBLE_LEN`]
+    pub const fn new(bits: &'static [Bit], max_errors: usize) -> Self {
+        assert!(
+            !bits.is_empty() && bits.len() <= MAX_PREAMBLE_LEN,
+            "unsupported preamble length"
+        );
+        Self {
+            bits,
+            packed: PackedBits::from_slice(bits),
+            max_errors,
+        }
+    }
+
+    pub const fn bits(&self) -> &'static [Bit] {
+        self.bits
+    }
+
+    pub const fn packed(&self) -> PackedBits<1> {
+        self.packed
+    }
+
+    pub const fn len(&self) -> usize {
+        self.bits.len()
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.bits.is_empty()
+    }
+
+    pub const fn max_errors(&self) -> usize {
+        self.max_errors
+    }
+}
+
+impl Default for Preamble {
+    fn default() -> Self {
+        Self::LEGACY
+    }
+}
+
+// the bits are a static, so only a preamble we know can be read back
+#[cfg(feature = "serde")]
+impl serde::Serialize for Preamble {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut preamble = serializer.serialize_struct("Preamble", 2)?;
+        preamble.serialize_field("bits", self.bits)?;
+        preamble.serialize_field("max_errors", &self.max_errors)?;
+        preamble.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Preamble {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(serde::Deserialize)]
+        #[serde(rename = "Preamble")]
+        struct Fields {
+            bits: heapless::Vec<Bit, MAX_PREAMBLE_LEN>,
+            max_errors: usize,
+        }
+
+        let fields = Fields::deserialize(deserializer)?;
+        [
+            Preamble::LEGACY,
+            Preamble::BARKER_13,
+            Preamble::M_SEQUENCE_15,
+        ]
+        .into_iter()
+        .find(|preamble| preamble.bits == fields.bits.as_slice())
+        .map(|preamble| Preamble::new(preamble.bits, fields.max_errors))
+        .ok_or_else(|| serde::de::Error::custom("not one of the preset preambles"))
+    }
+}
+
+/// where a preamble was found and how cleanly
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PreambleMatch {
+    /// index of the preamble's first bit on the line. a decoder counts every
+    /// bit it's given, those of the frames in between included
+    pub position: u64,
+    /// bits that didn't agree with the preamble
+    pub errors: usize,
+    pub len: usize,
+}
+
+impl PreambleMatch {
+    /// agreeing bits minus disagreeing ones, `len` for a perfect match
+    pub const fn correlation(&self) -> isize {
+        self.len as isize - 2 * self.errors as isize
+    }
+}
+
+/// shifts the line through a register and correlates it against a
+/// [`Preamble`], reporting the first alignment with few enough errors. each
+/// bit costs a shift, an xor and a popcount, whatever the preamble's length.
+/// shared by every decoder that frames its payload with a preamble.
+#[derive(Clone, Debug)]
+pub struct StartDetector {
+    preamble: Preamble,
+    // the latest bits, newest in the lowest place
+    window: u32,
+    // how many of them are from since the last match or reset
+    filled: usize,
+    seen: u64,
+    last: Option<PreambleMatch>,
+}
+
+impl Default for StartDetector {
+    fn default() -> Self {
+        Self::new(Preamble::LEGACY)
+    }
+}
+
+impl StartDetector {
+    pub const fn new(preamble: Preamble) -> Self {
+        Self {
+            preamble,
+            window: 0,
+            filled: 0,
+            seen: 0,
+            last: None,
+        }
+    }
+
+    pub const fn preamble(&self) -> Preamble {
+        self.preamble
+    }
+
+    /// the most recent match, kept around after the frame it started
+    pub const fn last_match(&self) -> Option<PreambleMatch> {
+        self.last
+    }
+
+    /// reports a match when `bit` completes the preamble, the window is
+    /// cleared so the same bits can't trigger it twice
+    // runs for every bit between frames, so let callers in other crates
+    // inline it
+    #[inline]
+    pub fn push(&mut self, bit: Bit) -> Option<PreambleMatch> {
+        let len = self.preamble.len();
+        self.seen += 1;
+        self.window = self.window << 1 | (bit == Bit::Hi) as u32;
+        self.filled = (self.filled + 1).min(len);
+        if self.filled < len {
+            return None;
+        }
+
+        let mask = u32::MAX >> (MAX_PREAMBLE_LEN - len);
+        let diff = (self.window ^ self.preamble.packed.head()) & mask;
+        let errors = diff.count_ones() as usize;
+        if errors > self.preamble.max_errors {
+            return None;
+        }
+
+        self.filled = 0;
+        let found = PreambleMatch {
+            position: self.seen - self.preamble.len() as u64,
+            errors,
+            len: self.preamble.len(),
+        };
+        self.last = Some(found);
+        Some(found)
+    }
+
+    /// counts `bits` that were taken by something else, like the frame after
+    /// a match, so positions stay those of the whole line
+    #[inline]
+    pub fn skip(&mut self, bits: u64) {
+        self.seen += bits;
+    }
+
+    /// forgets the partial window, the match history and bit count are kept
+    pub fn reset(&mut self) {
+        self.filled = 0;
+    }
+}
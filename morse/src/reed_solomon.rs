@@ -0,0 +1,247 @@
+use crate::{ErrorKind, MorseError};
+
+/// most parity bytes a block can carry, enough to fix 16 bad bytes
+pub const MAX_PARITY: usize = 32;
+
+/// longest block, data and parity together
+pub const BLOCK_LEN: usize = 255;
+
+// GF(256) with the usual x^8 + x^4 + x^3 + x^2 + 1, generator 2
+const POLY: u16 = 0x11d;
+
+struct Tables {
+    exp: [u8; 512],
+    log: [u8; 256],
+}
+
+const TABLES: Tables = {
+    let mut exp = [0; 512];
+    let mut log = [0; 256];
+
+    let mut x: u16 = 1;
+    let mut i = 0;
+    while i < 255 {
+        exp[i] = x as u8;
+        log[x as usize] = i as u8;
+        x <<= 1;
+        if x & 0x100 != 0 {
+            x ^= POLY;
+        }
+        i += 1;
+    }
+    // doubled up so a product never needs its log sum reduced
+    while i < 512 {
+        exp[i] = exp[i - 255];
+        i += 1;
+    }
+
+    Tables { exp, log }
+};
+
+const fn mul(a: u8, b: u8) -> u8 {
+    if a == 0 || b == 0 {
+        return 0;
+    }
+    TABLES.exp[TABLES.log[a as usize] as usize + TABLES.log[b as usize] as usize]
+}
+
+const fn div(a: u8, b: u8) -> u8 {
+    if a == 0 {
+        return 0;
+    }
+    TABLES.exp[TABLES.log[a as usize] as usize + 255 - TABLES.log[b as usize] as usize]
+}
+
+const fn pow(power: usize) -> u8 {
+    TABLES.exp[power % 255]
+}
+
+// polynomials below are stored lowest degree first
+fn eval(poly: &[u8], x: u8) -> u8 {
+    poly.iter().rev().fold(0, |acc, coef| mul(acc, x) ^ coef)
+}
+
+/// systematic reed-solomon over GF(256): a block is sent as its data bytes
+/// followed by `parity` check bytes, and up to `parity / 2` bytes anywhere in
+/// it can be wrong and still come back. blocks shorter than [`BLOCK_LEN`] are
+/// fine, the missing bytes are taken to be zeros that were never sent.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+pub struct ReedSolomon {
+    parity: usize,
+    // g(x) = (x - 1)(x - a)...(x - a^(parity - 1)), highest degree first and
+    // without its leading 1, which is how the encoder divides by it
+    generator: [u8; MAX_PARITY],
+}
+
+impl ReedSolomon {
+    /// whether [`ReedSolomon::new`] takes `parity`
+    pub const fn supports(parity: usize) -> bool {
+        parity > 0 && parity <= MAX_PARITY
+    }
+
+    /// panics unless `parity` is between 1 and [`MAX_PARITY`]
+    pub const fn new(parity: usize) -> Self {
+        assert!(Self::supports(parity), "unsupported parity length");
+
+        // lowest degree first while it's being built up
+        let mut g = [0; MAX_PARITY + 1];
+        g[0] = 1;
+        let mut j = 0;
+        while j < parity {
+            let root = pow(j);
+            let mut i = j + 1;
+            while i > 0 {
+                g[i] = g[i - 1] ^ mul(g[i], root);
+                i -= 1;
+            }
+            g[0] = mul(g[0], root);
+            j += 1;
+        }
+
+        let mut generator = [0; MAX_PARITY];
+        let mut i = 0;
+        while i < parity {
+            generator[i] = g[parity - 1 - i];
+            i += 1;
+        }
+
+        Self { parity, generator }
+    }
+
+    pub const fn parity(&self) -> usize {
+        self.parity
+    }
+
+    /// data bytes that fit in one full block
+    pub const fn data_len(&self) -> usize {
+        BLOCK_LEN - self.parity
+    }
+
+    /// shifts one data byte into the running `remainder`, which ends up as the
+    /// block's parity bytes once the last one is in. it has to start zeroed
+    pub fn feed(&self, remainder: &mut [u8], byte: u8) {
+        let remainder = &mut remainder[..self.parity];
+        let feedback = byte ^ remainder[0];
+        remainder.copy_within(1.., 0);
+        remainder[self.parity - 1] = 0;
+        for (r, g) in remainder.iter_mut().zip(&self.generator) {
+            *r ^= mul(feedback, *g);
+        }
+    }
+
+    /// writes the parity bytes for `data` into the front of `parity`
+    pub fn encode(&self, data: &[u8], parity: &mut [u8]) {
+        parity[..self.parity].fill(0);
+        for byte in data {
+            self.feed(parity, *byte);
+        }
+    }
+
+    /// fixes a received block in place, data and parity together, returning how
+    /// many bytes had to be corrected
+    pub fn decode(&self, block: &mut [u8]) -> Result<usize, MorseError> {
+        if block.len() <= self.parity || block.len() > BLOCK_LEN {
+            return Err(ErrorKind::TooManyErrors.into());
+        }
+
+        let mut syndromes = [0; MAX_PARITY];
+        let syndromes = &mut syndromes[..self.parity];
+        if !self.syndromes(block, syndromes) {
+            return Ok(0);
+        }
+
+        let (locator, errors) = berlekamp_massey(syndromes);
+        if errors * 2 > self.parity {
+            return Err(ErrorKind::TooManyErrors.into());
+        }
+
+        // the error evaluator, S(x) * L(x) mod x^parity
+        let mut evaluator = [0; MAX_PARITY];
+        for (k, e) in evaluator[..self.parity].iter_mut().enumerate() {
+            *e = (0..=k).fold(0, |acc, i| acc ^ mul(locator[i], syndromes[k - i]));
+        }
+        let evaluator = &evaluator[..self.parity];
+
+        // formal derivative of the locator, only odd powers survive in GF(2^n)
+        let mut derivative = [0; MAX_PARITY + 1];
+        for i in (1..locator.len()).step_by(2) {
+            derivative[i - 1] = locator[i];
+        }
+
+        // chien search over every position that was actually sent, fixing
+        // each one with forney's formula as it's found
+        let len = block.len();
+        let mut found = 0;
+        for (i, byte) in block.iter_mut().enumerate() {
+            let degree = len - 1 - i;
+            let x_inv = pow(255 - degree % 255);
+            if eval(&locator, x_inv) != 0 {
+                continue;
+            }
+
+            let slope = eval(&derivative, x_inv);
+            if slope == 0 {
+                return Err(ErrorKind::TooManyErrors.into());
+            }
+            *byte ^= mul(pow(degree), div(eval(evaluator, x_inv), slope));
+            found += 1;
+        }
+
+        // roots that fell outside the block mean it was too far gone to place
+        if found != errors || self.syndromes(block, syndromes) {
+            return Err(ErrorKind::TooManyErrors.into());
+        }
+
+        Ok(found)
+    }
+
+    // true if any syndrome is non zero, meaning the block has errors
+    fn syndromes(&self, block: &[u8], syndromes: &mut [u8]) -> bool {
+        for (j, s) in syndromes.iter_mut().enumerate() {
+            let root = pow(j);
+            *s = block.iter().fold(0, |acc, byte| mul(acc, root) ^ byte);
+        }
+        syndromes.iter().any(|s| *s != 0)
+    }
+}
+
+// finds the shortest lfsr that generates the syndromes, the roots of which are
+// the inverse error positions. returns it with how many errors it describes
+fn berlekamp_massey(syndromes: &[u8]) -> ([u8; MAX_PARITY + 1], usize) {
+    let mut locator = [0; MAX_PARITY + 1];
+    let mut previous = [0; MAX_PARITY + 1];
+    locator[0] = 1;
+    previous[0] = 1;
+
+    let mut errors = 0;
+    let mut shift = 1;
+    let mut last_discrepancy = 1;
+
+    for r in 0..syndromes.len() {
+        let discrepancy = (1..=errors).fold(syndromes[r], |acc, i| {
+            acc ^ mul(locator[i], syndromes[r - i])
+        });
+
+        if discrepancy == 0 {
+            shift += 1;
+            continue;
+        }
+
+        let scale = div(discrepancy, last_discrepancy);
+        let before = locator;
+        for i in shift..locator.len() {
+            locator[i] ^= mul(scale, previous[i - shift]);
+        }
+
+        if 2 * errors <= r {
+            errors = r + 1 - errors;
+            previous = before;
+            last_discrepancy = discrepancy;
+            shift = 1;
+        } else {
+            shift += 1;
+        }
+    }
+
+    (locator, errors)
+}
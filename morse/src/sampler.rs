@@ -0,0 +1,49 @@
+use crate::Bit;
+
+/// turns an oversampled stream of thresholded readings back into one bit per
+/// time step, the way a UART receiver does: every edge restarts the bit clock,
+/// and a bit is taken in the middle of each step after it.
+///
+/// with one sample per step this passes every sample straight through.
+#[derive(Clone, Debug)]
+pub struct Sampler {
+    samples_per_bit: u32,
+    level: Bit,
+    // samples left until the next bit is taken
+    countdown: u32,
+}
+
+impl Sampler {
+    pub const fn new(samples_per_bit: u32) -> Self {
+        let samples_per_bit = if samples_per_bit == 0 {
+            1
+        } else {
+            samples_per_bit
+        };
+
+        Self {
+            samples_per_bit,
+            level: Bit::Lo,
+            countdown: samples_per_bit / 2 + 1,
+        }
+    }
+
+    pub const fn samples_per_bit(&self) -> u32 {
+        self.samples_per_bit
+    }
+
+    pub fn push(&mut self, sample: Bit) -> Option<Bit> {
+        if sample != self.level {
+            self.level = sample;
+            self.countdown = self.samples_per_bit / 2 + 1;
+        }
+
+        self.countdown -= 1;
+        if self.countdown > 0 {
+            return None;
+        }
+
+        self.countdown = self.samples_per_bit;
+        Some(sample)
+    }
+}
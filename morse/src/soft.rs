@@ -0,0 +1,200 @@
+use crate::{BIT_SEQUENCE_LEN, Bit, ErrorKind, MorseBit, MorseError};
+
+/// elements a path through the trellis remembers before its oldest one is
+/// decided, agreed on or not
+pub const TRACEBACK: usize = 16;
+
+/// a line bit before it's been decided: how likely it is to be Hi, as a log
+/// likelihood ratio. positive leans Hi, negative Lo, and the further from
+/// zero the surer.
+///
+/// an adc reading minus the threshold makes a fine one, as long as the noise
+/// is about the same on both levels, see [`crate::LinkConfig::soft_sample`]
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Hash, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Soft(pub i16);
+
+impl Soft {
+    /// a bit nobody has any doubt about
+    pub const fn certain(bit: Bit) -> Self {
+        match bit {
+            Bit::Hi => Soft(i16::MAX),
+            Bit::Lo => Soft(-i16::MAX),
+        }
+    }
+
+    /// the bit it leans towards, zero counts as Hi like a reading right on the
+    /// threshold does
+    pub const fn hard(self) -> Bit {
+        if self.0 < 0 { Bit::Lo } else { Bit::Hi }
+    }
+}
+
+impl From<Bit> for Soft {
+    fn from(bit: Bit) -> Self {
+        Soft::certain(bit)
+    }
+}
+
+// the elements a path went through that aren't decided yet, oldest in the
+// lowest bits, three to an element. plain bits so survivors are copied as
+// they move between states instead of cloned
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+struct Path {
+    elements: u64,
+    len: u8,
+}
+
+const _: () = assert!(3 * TRACEBACK <= u64::BITS as usize);
+
+impl Path {
+    const EMPTY: Self = Self {
+        elements: 0,
+        len: 0,
+    };
+
+    const ELEMENTS: [MorseBit; 5] = [
+        MorseBit::Dot,
+        MorseBit::Dash,
+        MorseBit::CharBreak,
+        MorseBit::WordBreak,
+        MorseBit::LineBreak,
+    ];
+
+    const fn is_full(&self) -> bool {
+        self.len as usize == TRACEBACK
+    }
+
+    fn front(&self) -> Option<MorseBit> {
+        match self.len {
+            0 => None,
+            _ => Some(Self::ELEMENTS[(self.elements & 0b111) as usize]),
+        }
+    }
+
+    fn starts_with(&self, m_bit: MorseBit) -> bool {
+        self.len > 0 && self.elements & 0b111 == m_bit as u64
+    }
+
+    fn pop_front(&mut self) {
+        if self.len > 0 {
+            self.elements >>= 3;
+            self.len -= 1;
+        }
+    }
+
+    // only a path that lost long ago gets a whole traceback behind, its
+    // oldest element is never going to be asked for
+    fn push_back(mut self, m_bit: MorseBit) -> Self {
+        if self.is_full() {
+            self.pop_front();
+        }
+        self.elements |= (m_bit as u64) << (3 * self.len as u32);
+        self.len += 1;
+        self
+    }
+}
+
+/// viterbi search over a [`crate::UnaryCode`]. state `k` is `k` Hi bits into the
+/// current element: a Hi moves a path one state up, and a Lo closes the
+/// element, taking the path back to state 0, but only from states whose run
+/// is an element. so every path is a valid element sequence, and a sample
+/// near the threshold just changes which one wins instead of failing.
+///
+/// an element comes out once every surviving path agrees on it, which on a
+/// clean line is about one element behind. a run of Hi too long for any
+/// element just costs every path, and should they all die anyway that's an
+/// error and the search starts over instead of going quiet
+#[derive(Clone, Debug)]
+pub(crate) struct UnaryTrellis {
+    // what each run of Hi bits stands for in the code
+    elements: [Option<MorseBit>; BIT_SEQUENCE_LEN],
+    // how well the best path into each state fits the line so far, None
+    // while no path reaches it. the best of them is always 0
+    metrics: [Option<i32>; BIT_SEQUENCE_LEN],
+    paths: [Path; BIT_SEQUENCE_LEN],
+}
+
+impl UnaryTrellis {
+    pub(crate) const fn new(elements: [Option<MorseBit>; BIT_SEQUENCE_LEN]) -> Self {
+        let mut metrics = [None; BIT_SEQUENCE_LEN];
+        metrics[0] = Some(0);
+        Self {
+            elements,
+            metrics,
+            paths: [Path::EMPTY; BIT_SEQUENCE_LEN],
+        }
+    }
+
+    pub(crate) fn reset(&mut self) {
+        *self = Self::new(self.elements);
+    }
+
+    pub(crate) fn push(&mut self, soft: Soft) -> Result<Option<MorseBit>, MorseError> {
+        let soft = soft.0 as i32;
+
+        // a Lo closes the element of the best path whose run is one
+        let mut closed = None;
+        for k in 0..BIT_SEQUENCE_LEN {
+            let (Some(metric), Some(m_bit)) = (self.metrics[k], self.elements[k]) else {
+                continue;
+            };
+            if closed.is_none_or(|(best, _)| best < metric - soft) {
+                closed = Some((metric - soft, self.paths[k].push_back(m_bit)));
+            }
+        }
+
+        // a Hi moves every path a state up, off the end for the longest run
+        self.metrics.copy_within(..BIT_SEQUENCE_LEN - 1, 1);
+        self.paths.copy_within(..BIT_SEQUENCE_LEN - 1, 1);
+        for metric in &mut self.metrics[1..] {
+            *metric = metric.map(|metric| metric + soft);
+        }
+        (self.metrics[0], self.paths[0]) = match closed {
+            Some((metric, path)) => (Some(metric), path),
+            None => (None, Path::EMPTY),
+        };
+
+        // keep the numbers small, only the differences matter
+        let mut best = 0;
+        for k in 1..BIT_SEQUENCE_LEN {
+            if self.metrics[k] >= self.metrics[best] {
+                best = k;
+            }
+        }
+        let Some(top) = self.metrics[best] else {
+            self.reset();
+            return Err(ErrorKind::UnknownBitSequence.into());
+        };
+        for metric in &mut self.metrics {
+            *metric = metric.map(|metric| metric - top);
+        }
+
+        Ok(self.decide(best))
+    }
+
+    fn decide(&mut self, best: usize) -> Option<MorseBit> {
+        let oldest = self.paths[best].front()?;
+
+        let agreed = self
+            .metrics
+            .iter()
+            .zip(&self.paths)
+            .all(|(metric, path)| metric.is_none() || path.starts_with(oldest));
+        if !agreed && !self.paths[best].is_full() {
+            return None;
+        }
+
+        // whoever still disagrees after a whole traceback isn't coming back
+        for (metric, path) in self.metrics.iter_mut().zip(&mut self.paths) {
+            if metric.is_some() && path.starts_with(oldest) {
+                path.pop_front();
+            } else {
+                *metric = None;
+                *path = Path::EMPTY;
+            }
+        }
+        Some(oldest)
+    }
+}
@@ -0,0 +1,195 @@
+use crate::{ErrorKind, INVERSE_MORSE_TABLE, MorseBit, MorseError, MorseSymbol};
+
+/// longest pattern in the tables (HH, eight dots)
+pub const MAX_DEPTH: usize = 8;
+
+const NODES: usize = 1 << (MAX_DEPTH + 1);
+
+/// dichotomic search tree laid out as a heap: the root is node 1, and node `n`
+/// has its dot child at `2n` and its dash child at `2n + 1`. a node index is
+/// therefore just the pattern read as bits behind a leading 1, so decoding is
+/// one shift per element and a single array load at the end.
+#[derive(PartialEq, Eq, Debug, Hash)]
+pub struct MorseTree {
+    nodes: [Option<MorseSymbol>; NODES],
+    // the single element patterns for ' ', '\n' and '\0', which have no place
+    // in the tree since they are not made of dots and dashes
+    breaks: [Option<MorseSymbol>; 3],
+}
+
+pub static DECODE_TREE: MorseTree = MorseTree::new(INVERSE_MORSE_TABLE);
+
+const fn break_slot(bit: MorseBit) -> Option<usize> {
+    match bit {
+        MorseBit::CharBreak => Some(0),
+        MorseBit::LineBreak => Some(1),
+        MorseBit::WordBreak => Some(2),
+        MorseBit::Dot | MorseBit::Dash => None,
+    }
+}
+
+impl MorseTree {
+    /// builds the tree from a pattern table. when two entries share a pattern
+    /// the first one wins, same as a linear scan of the table would
+    pub const fn new(table: &[(&[MorseBit], MorseSymbol)]) -> Self {
+        Self::empty().with_fallback(table)
+    }
+
+    pub(crate) const fn empty() -> Self {
+        Self {
+            nodes: [None; NODES],
+            breaks: [None; 3],
+        }
+    }
+
+    /// puts `symbol` at heap index `node`, for tables that work out their
+    /// patterns instead of listing them
+    pub(crate) const fn with_node(mut self, node: usize, symbol: MorseSymbol) -> Self {
+        self.nodes[node] = Some(symbol);
+        self
+    }
+
+    /// adds the entries of `table` whose patterns are still free
+    pub const fn with_fallback(self, table: &[(&[MorseBit], MorseSymbol)]) -> Self {
+        let Self {
+            mut nodes,
+            mut breaks,
+        } = self;
+
+        let mut i = 0;
+        while i < table.len() {
+            let (pattern, symbol) = table[i];
+            i += 1;
+
+            if pattern.len() == 1
+                && let Some(slot) = break_slot(pattern[0])
+            {
+                if breaks[slot].is_none() {
+                    breaks[slot] = Some(symbol);
+                }
+                continue;
+            }
+
+            assert!(pattern.len() <= MAX_DEPTH, "pattern too long for tree");
+
+            let mut node = 1;
+            let mut j = 0;
+            while j < pattern.len() {
+                node = match pattern[j] {
+                    MorseBit::Dot => node * 2,
+                    MorseBit::Dash => node * 2 + 1,
+                    _ => panic!("breaks can only appear on their own"),
+                };
+                j += 1;
+            }
+
+            if nodes[node].is_none() {
+                nodes[node] = Some(symbol);
+            }
+        }
+
+        Self { nodes, breaks }
+    }
+
+    pub const fn cursor(&self) -> TreeCursor<'_> {
+        TreeCursor {
+            tree: self,
+            node: 1,
+        }
+    }
+
+    pub fn decode(&self, sequence: &[MorseBit]) -> Result<MorseSymbol, MorseError> {
+        if let [bit] = sequence
+            && let Some(slot) = break_slot(*bit)
+        {
+            return self.breaks[slot].ok_or_else(|| {
+                MorseError::from(ErrorKind::UnknownMorseSequence).with_elements(sequence)
+            });
+        }
+
+        let mut cursor = self.cursor();
+        for bit in sequence {
+            cursor.push(*bit)?;
+        }
+        cursor.take()
+    }
+}
+
+/// walks a [`MorseTree`] one element at a time, so a receiver can descend
+/// while the dots and dashes of a character are still arriving
+#[derive(Copy, Clone, Debug)]
+pub struct TreeCursor<'a> {
+    tree: &'a MorseTree,
+    node: usize,
+}
+
+impl TreeCursor<'_> {
+    /// fails on anything but a dot or dash, or when no pattern in the tables
+    /// is that long. the error holds the elements so far plus `bit`
+    #[inline]
+    pub fn push(&mut self, bit: MorseBit) -> Result<(), MorseError> {
+        let next = match bit {
+            MorseBit::Dot => self.node * 2,
+            MorseBit::Dash => self.node * 2 + 1,
+            _ => NODES,
+        };
+
+        if next >= NODES {
+            return Err(self.unknown(Some(bit)));
+        }
+
+        self.node = next;
+        Ok(())
+    }
+
+    /// the symbol at the current node, if the elements so far spell one
+    #[inline]
+    pub fn symbol(&self) -> Option<MorseSymbol> {
+        self.tree.nodes[self.node]
+    }
+
+    /// what the elements so far spell in `tree` instead. nodes are patterns,
+    /// so it's the same lookup
+    #[inline]
+    pub fn symbol_in(&self, tree: &MorseTree) -> Option<MorseSymbol> {
+        tree.nodes[self.node]
+    }
+
+    /// the dots and dashes pushed since the last reset
+    pub fn elements(&self) -> impl Iterator<Item = MorseBit> + use<> {
+        // the node index is the pattern behind a leading 1, read it from the top
+        let (node, depth) = (self.node, self.node.ilog2());
+        (0..depth).rev().map(move |i| match node >> i & 1 {
+            0 => MorseBit::Dot,
+            _ => MorseBit::Dash,
+        })
+    }
+
+    // the elements so far plus `bit`, which spell nothing. the decoder pushes
+    // every element through here, so this stays out of its way
+    #[cold]
+    fn unknown(&self, bit: Option<MorseBit>) -> MorseError {
+        let mut elements = heapless::Vec::<_, { MAX_DEPTH + 1 }>::new();
+        elements.extend(self.elements().chain(bit));
+        MorseError::from(ErrorKind::UnknownMorseSequence).with_elements(&elements)
+    }
+
+    /// true while no element has been pushed since the last reset
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.node == 1
+    }
+
+    #[inline]
+    pub fn reset(&mut self) {
+        self.node = 1;
+    }
+
+    /// finishes the current character and goes back to the root
+    #[inline]
+    pub fn take(&mut self) -> Result<MorseSymbol, MorseError> {
+        let symbol = self.symbol().ok_or_else(|| self.unknown(None));
+        self.reset();
+        symbol
+    }
+}
@@ -0,0 +1,207 @@
+use morse::{
+    Bit, ErrorKind, FLAG_ENCRYPTED, FrameDecoder, FrameEncoder, FrameEvent, FrameHeader,
+    MessageType, MorseError, START_SEQUENCE,
+};
+
+fn receive(
+    decoder: &mut FrameDecoder<heapless::Vec<u8, 256>>,
+    bits: impl IntoIterator<Item = Bit>,
+) -> Result<(FrameHeader, Vec<u8>), MorseError> {
+    for bit in START_SEQUENCE.into_iter().chain(bits) {
+        if let Some(FrameEvent::FrameEnd(header)) = decoder.push(bit)? {
+            return Ok((header, decoder.message().collect()));
+        }
+    }
+    panic!("the frame never ended");
+}
+
+#[test]
+fn sealed_frames_need_a_key() {
+    let mut header = FrameHeader::new(MessageType::Text, 1, 20);
+    header.flags = FLAG_ENCRYPTED;
+    let frame = FrameEncoder::with_header(header, &[0x55; 20]);
+
+    let e = receive(&mut FrameDecoder::new(), frame).unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::AuthenticationFailed);
+}
+
+#[cfg(feature = "aead")]
+mod sealed {
+    use morse::{FLAG_COMPRESSED, FrameKey, RETIRED_SESSIONS, SESSION_LEN, TAG_LEN};
+
+    use super::*;
+
+    const MSG: &[u8] = b"the reading on the north sensor is at 1200";
+    const KEY: FrameKey =
+        FrameKey::from_hex("000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f");
+    const SESSION: u64 = 0x5eed_f00d_0a11_b007;
+
+    fn sealed(sequence: u16, flags: u8, buffer: &mut [u8]) -> FrameEncoder<'_> {
+        sealed_in(SESSION, sequence, flags, buffer)
+    }
+
+    fn sealed_in(session: u64, sequence: u16, flags: u8, buffer: &mut [u8]) -> FrameEncoder<'_> {
+        let mut header = FrameHeader::new(MessageType::Text, sequence, 0);
+        header.flags = flags;
+        FrameEncoder::sealed(header, session, MSG, &KEY, buffer).unwrap()
+    }
+
+    // whether `decoder` lets a frame numbered `sequence` in `session` through
+    fn accepts(
+        decoder: &mut FrameDecoder<heapless::Vec<u8, 256>>,
+        session: u64,
+        sequence: u16,
+    ) -> Result<(), ErrorKind> {
+        let mut buffer = [0; 128];
+        match receive(decoder, sealed_in(session, sequence, 0, &mut buffer)) {
+            Ok((header, message)) => {
+                assert_eq!(header.sequence, sequence);
+                assert_eq!(message, MSG);
+                Ok(())
+            }
+            Err(e) => Err(e.kind()),
+        }
+    }
+
+    #[test]
+    fn round_trips_with_the_key() {
+        for flags in [0, FLAG_COMPRESSED] {
+            let mut buffer = [0; 128];
+            let mut decoder = FrameDecoder::new().with_key(KEY);
+            let (header, message) = receive(&mut decoder, sealed(9, flags, &mut buffer)).unwrap();
+            assert_eq!(header.flags, flags | FLAG_ENCRYPTED);
+            assert_eq!(header.sequence, 9);
+            assert_eq!(message, MSG);
+        }
+    }
+
+    #[test]
+    fn the_line_never_sees_the_text() {
+        let mut first = [0; 128];
+        let mut second = [0; 128];
+        sealed(1, 0, &mut first);
+        sealed(2, 0, &mut second);
+
+        let len = SESSION_LEN + MSG.len() + TAG_LEN;
+        assert_eq!(first[..SESSION_LEN], SESSION.to_be_bytes());
+        assert_ne!(first[SESSION_LEN..][..MSG.len()], *MSG);
+        // every sequence number gets its own keystream
+        assert_ne!(first[..len], second[..len]);
+
+        // and so does every session, for the same sequence number
+        let mut other = [0; 128];
+        sealed_in(SESSION + 1, 1, 0, &mut other);
+        assert_ne!(first[SESSION_LEN..len], other[SESSION_LEN..len]);
+    }
+
+    #[test]
+    fn other_keys_and_plain_frames_are_refused() {
+        let mut buffer = [0; 128];
+        let other = FrameKey::new([7; 32]);
+        let mut decoder = FrameDecoder::new().with_key(other);
+        let e = receive(&mut decoder, sealed(1, 0, &mut buffer)).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::AuthenticationFailed);
+
+        // a spoofed frame with a good checksum but no seal
+        let mut decoder = FrameDecoder::new().with_key(KEY);
+        let plain = FrameEncoder::new(MessageType::Text, 1, MSG).unwrap();
+        let e = receive(&mut decoder, plain).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::AuthenticationFailed);
+    }
+
+    #[test]
+    fn tampering_is_caught() {
+        let len = SESSION_LEN + MSG.len() + TAG_LEN;
+        // a flipped bit in the payload, and in the session
+        for flipped in [SESSION_LEN + 4, 2] {
+            let mut buffer = [0; 128];
+            sealed(3, 0, &mut buffer);
+            buffer[flipped] ^= 0x20;
+
+            let mut header = FrameHeader::new(MessageType::Text, 3, len as u16);
+            header.flags = FLAG_ENCRYPTED;
+            let forged = FrameEncoder::with_header(header, &buffer[..len]);
+            let mut decoder = FrameDecoder::new().with_key(KEY);
+            let e = receive(&mut decoder, forged).unwrap_err();
+            assert_eq!(e.kind(), ErrorKind::AuthenticationFailed);
+        }
+    }
+
+    #[test]
+    fn replays_are_refused() {
+        let mut decoder = FrameDecoder::new().with_key(KEY);
+        assert_eq!(accepts(&mut decoder, SESSION, 5), Ok(()));
+        assert_eq!(
+            accepts(&mut decoder, SESSION, 5),
+            Err(ErrorKind::ReplayedFrame)
+        );
+        assert_eq!(
+            accepts(&mut decoder, SESSION, 4),
+            Err(ErrorKind::ReplayedFrame)
+        );
+        // lost frames are fine, the sequence only has to go up
+        assert_eq!(accepts(&mut decoder, SESSION, 9), Ok(()));
+
+        // a reset only drops the frame in progress
+        decoder.reset();
+        assert_eq!(
+            accepts(&mut decoder, SESSION, 9),
+            Err(ErrorKind::ReplayedFrame)
+        );
+    }
+
+    #[test]
+    fn a_new_session_starts_over() {
+        let mut decoder = FrameDecoder::new().with_key(KEY);
+        assert_eq!(accepts(&mut decoder, SESSION, 700), Ok(()));
+        // tx rebooted
+        assert_eq!(accepts(&mut decoder, SESSION + 1, 0), Ok(()));
+        assert_eq!(accepts(&mut decoder, SESSION + 1, 1), Ok(()));
+
+        // and the old session stays closed, even further along
+        assert_eq!(
+            accepts(&mut decoder, SESSION, 701),
+            Err(ErrorKind::ReplayedFrame)
+        );
+    }
+
+    #[test]
+    fn only_the_last_sessions_are_remembered() {
+        let mut decoder = FrameDecoder::new().with_key(KEY);
+        // one session in use and one more than fit behind it
+        for session in 0..RETIRED_SESSIONS as u64 + 2 {
+            assert_eq!(accepts(&mut decoder, session, 0), Ok(()));
+        }
+        for session in 1..=RETIRED_SESSIONS as u64 {
+            assert_eq!(
+                accepts(&mut decoder, session, 1),
+                Err(ErrorKind::ReplayedFrame)
+            );
+        }
+        // the oldest one fell out
+        assert_eq!(accepts(&mut decoder, 0, 1), Ok(()));
+    }
+
+    #[test]
+    fn forged_frames_leave_no_mark() {
+        let mut buffer = [0; 128];
+        let other = FrameKey::new([7; 32]);
+        let header = FrameHeader::new(MessageType::Text, 50, 0);
+        let forged = FrameEncoder::sealed(header, SESSION, MSG, &other, &mut buffer).unwrap();
+
+        let mut decoder = FrameDecoder::new().with_key(KEY);
+        let e = receive(&mut decoder, forged).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::AuthenticationFailed);
+        assert_eq!(accepts(&mut decoder, SESSION, 1), Ok(()));
+    }
+
+    #[test]
+    fn small_buffers_are_refused() {
+        let mut buffer = [0; 32];
+        let header = FrameHeader::new(MessageType::Text, 0, 0);
+        let e = FrameEncoder::sealed(header, SESSION, MSG, &KEY, &mut buffer)
+            .err()
+            .unwrap();
+        assert_eq!(e.kind(), ErrorKind::FullBuffer);
+    }
+}
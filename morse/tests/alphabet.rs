@@ -0,0 +1,155 @@
+use morse::{
+    Alphabet, Bit, DecoderEvent, ErrorKind, LineCode, LineDecoder, MorseBit, MorseDecoder,
+    MorseEncoder, MorseError, MorseSymbol, Prosign, START_SEQUENCE, UnaryCode, fold, voicing_mark,
+};
+
+fn send(alphabet: Alphabet, text: &str) -> Result<Vec<Bit>, MorseError> {
+    MorseEncoder::from(text).with_alphabet(alphabet).collect()
+}
+
+fn receive(alphabet: Alphabet, bits: &[Bit]) -> String {
+    let mut decoder = MorseDecoder::new().with_alphabet(alphabet);
+    let mut text = String::new();
+    for bit in START_SEQUENCE.iter().chain(bits) {
+        for event in decoder.push(*bit).unwrap() {
+            match event {
+                DecoderEvent::Symbol(MorseSymbol::Char(c)) => text.push(c),
+                DecoderEvent::Symbol(MorseSymbol::Prosign(p)) => panic!("{p:?} came through"),
+                DecoderEvent::FrameEnd => return text,
+                DecoderEvent::FrameStart => {}
+            }
+        }
+    }
+    panic!("the frame never ended");
+}
+
+fn round_trip(alphabet: Alphabet, text: &str) -> String {
+    receive(alphabet, &send(alphabet, text).unwrap())
+}
+
+// how many times `prosign` goes out in `bits`, as a whole char
+fn count(bits: &[Bit], prosign: Prosign) -> usize {
+    let mut decoder = UnaryCode::default().decoder();
+    let mut elements = Vec::new();
+    for bit in bits {
+        if let Some(m_bit) = decoder.push(*bit).unwrap() {
+            elements.push(m_bit);
+        }
+    }
+    elements
+        .split(|m_bit| !matches!(m_bit, MorseBit::Dot | MorseBit::Dash))
+        .filter(|pattern| *pattern == prosign.sequence())
+        .count()
+}
+
+#[test]
+fn every_alphabet_round_trips_its_own_letters() {
+    let cases = [
+        (Alphabet::Latin, "cq de n0call 73", "CQ DE N0CALL 73"),
+        (Alphabet::Cyrillic, "привет мир 73", "ПРИВЕТ МИР 73"),
+        (Alphabet::Greek, "καλημέρα κόσμε", "ΚΑΛΗΜΕΡΑ ΚΟΣΜΕ"),
+        (Alphabet::Hebrew, "שלום עולם", "שלומ עולמ"),
+        (Alphabet::Arabic, "أهلا 5", "اهلا 5"),
+        (Alphabet::Wabun, "イロハ ニホヘト", "イロハ ニホヘト"),
+    ];
+    for (alphabet, sent, received) in cases {
+        assert_eq!(round_trip(alphabet, sent), received, "{alphabet:?}");
+    }
+}
+
+#[test]
+fn every_letter_of_every_table_comes_back() {
+    for alphabet in Alphabet::ALL {
+        for (pattern, symbol) in alphabet.letters() {
+            let MorseSymbol::Char(c) = symbol else {
+                continue;
+            };
+            assert_eq!(alphabet.char_sequence(*c).unwrap(), *pattern, "{c}");
+            assert_eq!(alphabet.decode(pattern).unwrap(), *symbol, "{c}");
+        }
+    }
+}
+
+#[test]
+fn kana_shift_into_wabun_and_back_mid_message() {
+    let bits = send(Alphabet::Latin, "CQ ヤマ DE JA1").unwrap();
+    assert_eq!(count(&bits, Prosign::DO), 1);
+    assert_eq!(count(&bits, Prosign::SN), 1);
+    assert_eq!(receive(Alphabet::Latin, &bits), "CQ ヤマ DE JA1");
+
+    // SN goes back to whatever the frame started in
+    let text = "ДА ヤマ ДА";
+    assert_eq!(round_trip(Alphabet::Cyrillic, text), text);
+
+    // digits and spaces are the same in wabun, so they don't shift back
+    let bits = send(Alphabet::Latin, "ヤマ 73").unwrap();
+    assert_eq!(count(&bits, Prosign::SN), 0);
+    assert_eq!(receive(Alphabet::Latin, &bits), "ヤマ 73");
+
+    // a frame left in wabun doesn't carry over into the next
+    let mut decoder = MorseDecoder::new();
+    let next = send(Alphabet::Latin, "E").unwrap();
+    for bit in START_SEQUENCE.iter().chain(&bits) {
+        decoder.push(*bit).unwrap();
+    }
+    assert_eq!(decoder.active_alphabet(), Alphabet::Wabun);
+    let events: Vec<DecoderEvent> = START_SEQUENCE
+        .iter()
+        .chain(&next)
+        .flat_map(|bit| decoder.push(*bit).unwrap())
+        .collect();
+    assert!(events.contains(&DecoderEvent::Symbol(MorseSymbol::Char('E'))));
+}
+
+#[test]
+fn voiced_kana_go_out_as_two_chars() {
+    assert_eq!(voicing_mark('ガ'), Some(('カ', '゛')));
+    assert_eq!(voicing_mark('ヅ'), Some(('ツ', '゛')));
+    assert_eq!(voicing_mark('ボ'), Some(('ホ', '゛')));
+    assert_eq!(voicing_mark('パ'), Some(('ハ', '゜')));
+    assert_eq!(voicing_mark('ヴ'), Some(('ウ', '゛')));
+    assert_eq!(voicing_mark('カ'), None);
+    assert_eq!(voicing_mark('ハ'), None);
+
+    assert_eq!(round_trip(Alphabet::Wabun, "ガパ"), "カ゛ハ゜");
+}
+
+#[test]
+fn hiragana_and_small_kana_fold_into_katakana() {
+    assert_eq!(fold('か'), 'カ');
+    assert_eq!(fold('っ'), 'ツ');
+    assert_eq!(fold('ャ'), 'ヤ');
+    assert_eq!(fold('\u{3099}'), '゛');
+    assert_eq!(round_trip(Alphabet::Wabun, "がっこう"), "カ゛ツコウ");
+    assert_eq!(round_trip(Alphabet::Latin, "きっぷ"), "キツフ゜");
+}
+
+#[test]
+fn other_scripts_fold_their_variants() {
+    assert_eq!(fold('ё'), 'Е');
+    assert_eq!(fold('ώ'), 'Ω');
+    assert_eq!(fold('ς'), 'Σ');
+    assert_eq!(fold('ץ'), 'צ');
+    assert_eq!(fold('إ'), 'ا');
+    assert_eq!(round_trip(Alphabet::Cyrillic, "ёж"), "ЕЖ");
+}
+
+// most alphabets hand the latin patterns to their own letters, so the latin
+// letters they shadow can't be sent at all. Q's pattern is Щ in cyrillic
+#[test]
+fn shadowed_latin_letters_are_unsupported() {
+    assert_eq!(
+        Alphabet::Cyrillic.char_sequence('q').unwrap_err().kind(),
+        ErrorKind::UnsupportedChar('q')
+    );
+    assert_eq!(
+        send(Alphabet::Cyrillic, "QSL").unwrap_err().kind(),
+        ErrorKind::UnsupportedChar('Q')
+    );
+    // the same pattern still decodes, as the cyrillic letter
+    let q = morse::char_sequence('Q').unwrap();
+    assert_eq!(
+        Alphabet::Cyrillic.decode(q).unwrap(),
+        MorseSymbol::Char('Щ')
+    );
+}
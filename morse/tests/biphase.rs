@@ -0,0 +1,134 @@
+use morse::{Biphase, Bit, DecoderEvent, MorseDecoder, MorseEncoder, MorseSymbol, START_SEQUENCE};
+
+const MSG: &str = "UCSC CSE 121 ABCDEFGHIJKLM NOPQRSTUVWXYZ 12345 67890";
+
+const SAMPLES_PER_HALF_BIT: f64 = 8.0;
+
+/// a whole transmission in half bits, with some idle line on either side
+fn transmission(mode: Biphase, msg: &str) -> Vec<Bit> {
+    let bits = START_SEQUENCE
+        .into_iter()
+        .chain(MorseEncoder::from(msg).map(Result::unwrap));
+
+    let mut line = vec![Bit::Lo; 40];
+    line.extend(mode.encode(bits));
+    line.extend([Bit::Lo; 40]);
+    line
+}
+
+/// samples the line at the receiver's nominal rate while the transmitter's
+/// half bit lasts `skew(t)` times as long as it should, t being the position
+/// in the transmission from 0 to 1
+fn sample(line: &[Bit], skew: impl Fn(f64) -> f64) -> Vec<Bit> {
+    let mut samples = Vec::new();
+    let mut half = 0;
+    let mut edge = 0.0;
+    let mut t = 0.0;
+
+    while half < line.len() {
+        let len = SAMPLES_PER_HALF_BIT * skew(half as f64 / line.len() as f64);
+        while t < edge + len {
+            samples.push(line[half]);
+            t += 1.0;
+        }
+        edge += len;
+        half += 1;
+    }
+
+    samples
+}
+
+fn receive(mode: Biphase, samples: &[Bit]) -> Vec<String> {
+    let mut biphase = mode.decoder(SAMPLES_PER_HALF_BIT as u32);
+    let mut decoder = MorseDecoder::new();
+    let mut frames = Vec::new();
+    let mut msg = String::new();
+
+    for sample in samples {
+        let bit = match biphase.push(*sample) {
+            Ok(Some(bit)) => bit,
+            Ok(None) => continue,
+            Err(e) => panic!("lost the clock: {e:?}"),
+        };
+
+        for event in decoder.push(bit).expect("should decode") {
+            match event {
+                DecoderEvent::FrameStart => msg.clear(),
+                DecoderEvent::Symbol(MorseSymbol::Char(c)) => msg.push(c),
+                DecoderEvent::Symbol(symbol) => panic!("unexpected {symbol:?}"),
+                DecoderEvent::FrameEnd => frames.push(msg.clone()),
+            }
+        }
+    }
+
+    frames
+}
+
+fn round_trip(mode: Biphase, skew: impl Fn(f64) -> f64) {
+    let samples = sample(&transmission(mode, MSG), skew);
+    assert_eq!(receive(mode, &samples), [MSG]);
+}
+
+#[test]
+fn manchester_half_bits() {
+    let line: Vec<Bit> = Biphase::Manchester
+        .encode([Bit::Hi, Bit::Lo, Bit::Lo])
+        .collect();
+    assert_eq!(line, [Bit::Hi, Bit::Lo, Bit::Lo, Bit::Hi, Bit::Lo, Bit::Hi]);
+}
+
+#[test]
+fn differential_half_bits() {
+    let line: Vec<Bit> = Biphase::DifferentialManchester
+        .encode([Bit::Hi, Bit::Lo, Bit::Hi])
+        .collect();
+    assert_eq!(line, [Bit::Lo, Bit::Hi, Bit::Lo, Bit::Hi, Bit::Hi, Bit::Lo]);
+}
+
+#[test]
+fn locked_clocks() {
+    for mode in [Biphase::Manchester, Biphase::DifferentialManchester] {
+        round_trip(mode, |_| 1.0);
+    }
+}
+
+#[test]
+fn fast_and_slow_transmitter() {
+    for mode in [Biphase::Manchester, Biphase::DifferentialManchester] {
+        for skew in [0.85, 0.93, 1.07, 1.15] {
+            round_trip(mode, |_| skew);
+        }
+    }
+}
+
+#[test]
+fn drifting_transmitter() {
+    for mode in [Biphase::Manchester, Biphase::DifferentialManchester] {
+        // speeds up by 20% over the frame, then the other way around
+        round_trip(mode, |t| 0.9 + 0.2 * t);
+        round_trip(mode, |t| 1.1 - 0.2 * t);
+    }
+}
+
+#[test]
+fn tracks_the_half_bit_length() {
+    let mode = Biphase::Manchester;
+    let samples = sample(&transmission(mode, MSG), |_| 1.2);
+
+    let mut decoder = mode.decoder(SAMPLES_PER_HALF_BIT as u32);
+    for sample in samples {
+        decoder.push(sample).expect("should keep lock");
+    }
+    let expected = (SAMPLES_PER_HALF_BIT * 1.2).round() as u32;
+    assert_eq!(decoder.samples_per_half_bit(), expected);
+}
+
+#[test]
+fn back_to_back_frames() {
+    for mode in [Biphase::Manchester, Biphase::DifferentialManchester] {
+        let mut line = transmission(mode, "CQ");
+        line.extend(transmission(mode, "DE K6ABC"));
+        let samples = sample(&line, |_| 1.05);
+        assert_eq!(receive(mode, &samples), ["CQ", "DE K6ABC"]);
+    }
+}
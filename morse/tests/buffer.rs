@@ -0,0 +1,88 @@
+use morse::{
+    Alphabet, Bit, BitSequece, Buffer, CHAR_SEQUENCE_LEN, ErrorKind, FrameDecoder, FrameEncoder,
+    FrameEvent, MessageType, MorseBit, MorseBitSequence, MorseConversion, MorseError, Prosign,
+    START_SEQUENCE, parse_bits, parse_morse,
+};
+
+fn receive<B: Buffer<u8>>(
+    decoder: &mut FrameDecoder<B>,
+    bits: impl IntoIterator<Item = Bit>,
+) -> Result<Vec<u8>, MorseError> {
+    for bit in START_SEQUENCE.into_iter().chain(bits) {
+        if let Some(FrameEvent::FrameEnd(_)) = decoder.push(bit)? {
+            return Ok(decoder.message().collect());
+        }
+    }
+    panic!("the frame never ended");
+}
+
+#[test]
+fn fixed_buffers_refuse_what_doesnt_fit() {
+    let msg = [0x42; 100];
+    let frame = || FrameEncoder::new(MessageType::Data, 1, &msg).unwrap();
+
+    let mut small = FrameDecoder::<heapless::Vec<u8, 64>>::new();
+    let e = receive(&mut small, frame()).unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::FullBuffer);
+
+    let mut big = FrameDecoder::<heapless::Vec<u8, 128>>::new();
+    assert_eq!(receive(&mut big, frame()).unwrap(), msg);
+}
+
+#[test]
+fn sequences_take_any_capacity() {
+    let text = "... --- ... ".repeat(40);
+    assert_eq!(
+        parse_morse::<MorseBitSequence<4>>(&text)
+            .unwrap_err()
+            .kind(),
+        ErrorKind::FullBuffer
+    );
+    // past the default capacity
+    assert_eq!(
+        parse_morse::<MorseBitSequence>(&text).unwrap_err().kind(),
+        ErrorKind::FullBuffer
+    );
+    assert!(parse_morse::<MorseBitSequence<512>>(&text).is_ok());
+
+    let bits = "HHHL".repeat(40);
+    let e = parse_bits::<BitSequece<8>>(&bits).unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::FullBuffer);
+    assert_eq!(parse_bits::<BitSequece<160>>(&bits).unwrap().len(), 160);
+}
+
+#[test]
+fn one_char_fits_its_own_capacity() {
+    // the longest there are, a voiced kana is two patterns and a break
+    for c in ['ガ', 'パ'] {
+        let seq: MorseBitSequence<CHAR_SEQUENCE_LEN> =
+            c.to_morse_bit_sequence_in(Alphabet::Wabun).unwrap();
+        assert!(seq.contains(&MorseBit::CharBreak), "{c}");
+    }
+    let seq: MorseBitSequence<CHAR_SEQUENCE_LEN> = Prosign::HH.to_morse_bit_sequence().unwrap();
+    assert_eq!(seq, Prosign::HH.sequence());
+
+    let e = 'ガ'
+        .to_morse_bit_sequence_in::<MorseBitSequence<4>>(Alphabet::Wabun)
+        .unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::FullBuffer);
+}
+
+#[cfg(feature = "alloc")]
+mod growing {
+    use morse::MORSE_SEQUENCE_LEN;
+
+    use super::*;
+
+    #[test]
+    fn vecs_take_anything() {
+        let msg: Vec<u8> = (0..2000).map(|i| i as u8).collect();
+        let frame = FrameEncoder::new(MessageType::Data, 1, &msg).unwrap();
+        let mut decoder = FrameDecoder::<Vec<u8>>::new();
+        assert_eq!(receive(&mut decoder, frame).unwrap(), msg);
+
+        let text = "... --- ... ".repeat(200);
+        let elements: Vec<MorseBit> = parse_morse(&text).unwrap();
+        assert!(elements.len() > MORSE_SEQUENCE_LEN);
+    }
+}
@@ -0,0 +1,66 @@
+use morse::{
+    Bit, ByteDecoder, ByteEncoder, ByteEvent, DecoderState, ErrorKind, MorseError, START_SEQUENCE,
+};
+
+// line bits per uart byte, start and stop included
+const FRAME_BITS: usize = 10;
+
+type Decoder = ByteDecoder<heapless::Vec<u8, 64>>;
+
+fn events(decoder: &mut Decoder, bits: &[Bit]) -> Result<Vec<ByteEvent>, MorseError> {
+    let mut seen = Vec::new();
+    for bit in START_SEQUENCE.iter().chain(bits) {
+        seen.extend(decoder.push(*bit)?);
+    }
+    Ok(seen)
+}
+
+#[test]
+fn bytes_are_framed_like_a_uart() {
+    use Bit::*;
+
+    let bits: Vec<Bit> = ByteEncoder::from(&[0x35][..]).collect();
+    // start, 0x35 least significant first, stop, then a break of all Lo
+    assert_eq!(bits[..FRAME_BITS], [Lo, Hi, Lo, Hi, Lo, Hi, Hi, Lo, Lo, Hi]);
+    assert_eq!(bits[FRAME_BITS..], [Lo; FRAME_BITS]);
+}
+
+#[test]
+fn the_break_ends_the_frame() {
+    let msg = [0x00, 0xff, 0x42, 0x00];
+    let bits: Vec<Bit> = ByteEncoder::from(&msg[..]).collect();
+    let mut decoder = Decoder::new();
+
+    let mut expected = vec![ByteEvent::FrameStart];
+    expected.extend(msg.map(ByteEvent::Byte));
+    expected.push(ByteEvent::FrameEnd);
+    assert_eq!(events(&mut decoder, &bits).unwrap(), expected);
+    assert_eq!(decoder.payload(), msg);
+    assert_eq!(decoder.state(), DecoderState::Processing);
+}
+
+#[test]
+fn the_line_may_idle_hi_between_bytes() {
+    let bits: Vec<Bit> = ByteEncoder::from(&b"HI"[..]).collect();
+    let mut idling = Vec::new();
+    for byte in bits.chunks(FRAME_BITS) {
+        idling.extend([Bit::Hi; 3]);
+        idling.extend(byte);
+    }
+
+    let mut decoder = Decoder::new();
+    events(&mut decoder, &idling).unwrap();
+    assert_eq!(decoder.payload(), b"HI");
+}
+
+#[test]
+fn a_lost_stop_bit_is_a_framing_error() {
+    let mut bits: Vec<Bit> = ByteEncoder::from(&b"OK"[..]).collect();
+    bits[2 * FRAME_BITS - 1] = Bit::Lo;
+
+    let mut decoder = Decoder::new();
+    let e = events(&mut decoder, &bits).unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::UnknownBitSequence);
+    assert_eq!(e.position().map(|p| p.symbol), Some(1));
+    assert_eq!(decoder.state(), DecoderState::WaitingForStart);
+}
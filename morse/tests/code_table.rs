@@ -0,0 +1,135 @@
+use morse::{
+    Alphabet, Checksum, CodeTable, DecoderEvent, ENGLISH, INVERSE_MORSE_TABLE, MorseBit,
+    MorseDecoder, MorseEncoder, MorseSymbol, START_SEQUENCE, UnaryCode,
+};
+
+const MSG: &str = "THE QUICK BROWN FOX JUMPS OVER THE LAZY DOG 73";
+
+fn bits(msg: &str, alphabet: Alphabet) -> usize {
+    MorseEncoder::from(msg)
+        .with_alphabet(alphabet)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap()
+        .len()
+}
+
+fn receive(msg: &str, alphabet: Alphabet) -> String {
+    let mut decoder = MorseDecoder::new()
+        .with_alphabet(alphabet)
+        .with_checksum(Checksum::Crc16);
+    let encoder = MorseEncoder::from(msg)
+        .with_alphabet(alphabet)
+        .with_checksum(Checksum::Crc16);
+
+    let mut text = String::new();
+    for bit in START_SEQUENCE
+        .into_iter()
+        .chain(encoder.map(Result::unwrap))
+    {
+        for event in decoder.push(bit).unwrap() {
+            if let DecoderEvent::Symbol(MorseSymbol::Char(c)) = event {
+                text.push(c);
+            }
+        }
+    }
+    text
+}
+
+#[test]
+fn round_trips_through_the_codec() {
+    assert_eq!(receive(MSG, Alphabet::Custom(&ENGLISH)), MSG);
+}
+
+#[test]
+fn trailers_read_back_as_hex_in_every_alphabet() {
+    let alphabets = Alphabet::ALL
+        .into_iter()
+        .chain([Alphabet::Custom(&ENGLISH)]);
+    for alphabet in alphabets {
+        // numbers go out the same in every alphabet, their trailers don't
+        let mut unseen: Vec<char> = "0123456789ABCDEF".chars().collect();
+        for n in 0.. {
+            let msg = n.to_string();
+            assert_eq!(receive(&msg, alphabet), msg, "{alphabet:?}");
+
+            let trailer = format!("{:04X}", Checksum::Crc16.compute(msg.as_bytes()));
+            unseen.retain(|digit| !trailer.contains(*digit));
+            if unseen.is_empty() {
+                break;
+            }
+        }
+    }
+}
+
+#[test]
+fn trailers_with_moved_letters_pass() {
+    // its trailer is 8C1V before the fix, with a C that was read as an M
+    assert_eq!(
+        receive("ROOM 456 X", Alphabet::Custom(&ENGLISH)),
+        "ROOM 456 X"
+    );
+}
+
+#[test]
+fn common_letters_get_the_cheap_patterns() {
+    assert_eq!(ENGLISH.pattern('E'), Some(&[MorseBit::Dot][..]));
+    assert_eq!(ENGLISH.pattern('T'), Some(&[MorseBit::Dot; 2][..]));
+    assert_eq!(ENGLISH.symbols().next().map(|(c, _)| c), Some('E'));
+    // digits weren't in the profile and keep their latin patterns
+    assert_eq!(ENGLISH.pattern('7'), None);
+    assert_eq!(
+        Alphabet::Custom(&ENGLISH).char_sequence('7'),
+        Alphabet::Latin.char_sequence('7')
+    );
+}
+
+#[test]
+fn english_text_costs_less_than_itu_morse() {
+    assert!(bits(MSG, Alphabet::Custom(&ENGLISH)) < bits(MSG, Alphabet::Latin));
+    let numbers = "ROOM 456 AT 1890";
+    assert!(bits(numbers, Alphabet::Custom(&ENGLISH)) < bits(numbers, Alphabet::Latin));
+}
+
+#[test]
+fn only_letters_are_moved() {
+    let english = Alphabet::Custom(&ENGLISH);
+    let patterns = INVERSE_MORSE_TABLE.iter().filter(|(pattern, _)| {
+        pattern
+            .iter()
+            .all(|m_bit| matches!(m_bit, MorseBit::Dot | MorseBit::Dash))
+    });
+    for (pattern, symbol) in patterns {
+        match symbol {
+            MorseSymbol::Char(c) if c.is_ascii_alphabetic() => {}
+            // every digit and punctuation mark goes out the way it does in latin
+            MorseSymbol::Char(c) => {
+                assert_eq!(english.char_sequence(*c).unwrap(), *pattern, "{c:?}");
+            }
+            // AR and BT still decode as + and =, like in latin
+            MorseSymbol::Prosign(p) => assert_eq!(
+                english.decode(pattern).unwrap(),
+                Alphabet::Latin.decode(pattern).unwrap(),
+                "{p:?}"
+            ),
+        }
+    }
+
+    // so numbers never need the detour through wabun
+    let digits = MorseEncoder::from("0123456789")
+        .with_alphabet(english)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    let latin = MorseEncoder::from("0123456789")
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(digits, latin);
+}
+
+#[test]
+fn costs_decide_the_shape() {
+    // with dashes as cheap as dots, the second letter gets one
+    let table = CodeTable::new(&[('A', 2), ('B', 1)], 1, 1);
+    assert_eq!(table.pattern('B'), Some(&[MorseBit::Dash][..]));
+    let table = CodeTable::for_unary(&[('A', 2), ('B', 1)], UnaryCode::COMPACT);
+    assert_eq!(table.pattern('B'), Some(&[MorseBit::Dot; 2][..]));
+}
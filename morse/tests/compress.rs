@@ -0,0 +1,97 @@
+use morse::{
+    Bit, Compress, Decompress, ErrorKind, FLAG_COMPRESSED, FrameDecoder, FrameEncoder, FrameEvent,
+    FrameHeader, MessageType, START_SEQUENCE, compressed_len,
+};
+
+const MESSAGES: &[&str] = &[
+    "UCSC CSE 121 ABCDEFGHIJKLM NOPQRSTUVWXYZ 12345 67890",
+    "Hello ESP32",
+    "the reading on the north sensor is at 1200, check that it is not stuck",
+    "",
+];
+
+fn round_trip(bytes: &[u8]) -> Vec<u8> {
+    let compressed: Vec<u8> = Compress::new(bytes).collect();
+    assert_eq!(compressed.len(), compressed_len(bytes));
+    Decompress::new(&compressed).unwrap().collect()
+}
+
+fn receive(bits: impl IntoIterator<Item = Bit>) -> (FrameHeader, Vec<u8>) {
+    let mut decoder = FrameDecoder::<heapless::Vec<u8, 256>>::new();
+    for bit in START_SEQUENCE.into_iter().chain(bits) {
+        if let Some(FrameEvent::FrameEnd(header)) = decoder.push(bit).unwrap() {
+            return (header, decoder.message().collect());
+        }
+    }
+    panic!("the frame never ended");
+}
+
+#[test]
+fn anything_round_trips() {
+    for msg in MESSAGES {
+        assert_eq!(round_trip(msg.as_bytes()), msg.as_bytes());
+    }
+    let binary: Vec<u8> = (0..=255).collect();
+    assert_eq!(round_trip(&binary), binary);
+}
+
+#[test]
+fn text_gets_shorter() {
+    for msg in &MESSAGES[..3] {
+        assert!(compressed_len(msg.as_bytes()) < msg.len(), "{msg}");
+    }
+}
+
+#[test]
+fn frames_carry_the_flag() {
+    let msg = MESSAGES[2].as_bytes();
+    let (header, message) = receive(FrameEncoder::compressed(MessageType::Text, 7, msg).unwrap());
+    assert_eq!(header.flags & FLAG_COMPRESSED, FLAG_COMPRESSED);
+    assert_eq!(header.length as usize, compressed_len(msg));
+    assert_eq!(message, msg);
+}
+
+#[test]
+fn frames_that_wont_shrink_go_as_they_are() {
+    let binary = [0xde, 0xad, 0xbe, 0xef];
+    let (header, message) =
+        receive(FrameEncoder::compressed(MessageType::Data, 7, &binary).unwrap());
+    assert_eq!(header.flags, 0);
+    assert_eq!(message, binary);
+}
+
+#[test]
+fn payloads_too_long_even_compressed_are_refused() {
+    // every "th" shrinks to a byte, which still leaves more than a length
+    // field can say
+    let msg = "xq th".repeat(20_000);
+    assert!(compressed_len(msg.as_bytes()) > u16::MAX as usize);
+    assert!(compressed_len(msg.as_bytes()) < msg.len());
+    assert_eq!(
+        FrameEncoder::compressed(MessageType::Text, 7, msg.as_bytes())
+            .err()
+            .map(|e| e.kind()),
+        Some(ErrorKind::FullBuffer)
+    );
+}
+
+#[test]
+fn broken_payloads_are_dropped() {
+    // a lone escape at the end
+    assert_eq!(
+        Decompress::new(b"ok\xff").unwrap_err().kind(),
+        ErrorKind::MalformedFrame
+    );
+
+    let mut header = FrameHeader::new(MessageType::Text, 1, 1);
+    header.flags = FLAG_COMPRESSED;
+    let mut decoder = FrameDecoder::<heapless::Vec<u8, 64>>::new();
+    let bits = START_SEQUENCE
+        .into_iter()
+        .chain(FrameEncoder::with_header(header, &[0xff]));
+    let e = bits
+        .map(|bit| decoder.push(bit))
+        .find_map(Result::err)
+        .unwrap();
+    assert_eq!(e.kind(), ErrorKind::MalformedFrame);
+}
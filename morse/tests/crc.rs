@@ -0,0 +1,71 @@
+use morse::{
+    Bit, Checksum, DecoderEvent, ErrorKind, MorseDecoder, MorseEncoder, MorseError, MorseSymbol,
+    START_SEQUENCE,
+};
+
+const CHECK: &[u8] = b"123456789";
+
+fn receive(bits: &[Bit]) -> Result<String, MorseError> {
+    let mut decoder = MorseDecoder::new().with_checksum(Checksum::Crc16);
+    let mut text = String::new();
+    for bit in START_SEQUENCE.iter().chain(bits) {
+        for event in decoder.push(*bit)? {
+            match event {
+                DecoderEvent::Symbol(MorseSymbol::Char(c)) => text.push(c),
+                DecoderEvent::FrameEnd => return Ok(text),
+                _ => {}
+            }
+        }
+    }
+    panic!("the frame never ended");
+}
+
+fn frame(text: &str) -> Vec<Bit> {
+    MorseEncoder::from(text)
+        .with_checksum(Checksum::Crc16)
+        .map(Result::unwrap)
+        .collect()
+}
+
+#[test]
+fn the_standard_check_values() {
+    assert_eq!(Checksum::Crc8.compute(CHECK), 0xf4);
+    assert_eq!(Checksum::Crc16.compute(CHECK), 0x29b1);
+    assert_eq!(Checksum::None.compute(CHECK), 0);
+}
+
+#[test]
+fn digests_add_up_piece_by_piece() {
+    for checksum in [Checksum::Crc8, Checksum::Crc16] {
+        let mut digest = checksum.digest();
+        for chunk in CHECK.chunks(2) {
+            digest.update(chunk);
+        }
+        assert_eq!(digest.value(), checksum.compute(CHECK), "{checksum:?}");
+    }
+}
+
+#[test]
+fn clean_frames_pass() {
+    assert_eq!(receive(&frame("HELLO")).unwrap(), "HELLO");
+}
+
+#[test]
+fn a_corrupted_char_is_caught() {
+    // H is four dots, one of them lost on the way makes it an S
+    let mut bits = frame("HELLO");
+    assert_eq!(bits[0], Bit::Lo);
+    bits.remove(0);
+
+    let e = receive(&bits).unwrap_err();
+    let expected = Checksum::Crc16.compute(b"HELLO");
+    assert_eq!(
+        e.kind(),
+        ErrorKind::ChecksumMismatch {
+            expected,
+            actual: Checksum::Crc16.compute(b"SELLO"),
+        }
+    );
+    // the trailer is read as it was sent
+    assert_eq!(e.partial(), format!("SELLO{expected:04X}"));
+}
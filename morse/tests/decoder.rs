@@ -0,0 +1,128 @@
+use morse::{
+    Bit, BitSequece, DecoderEvent, DecoderState, LineCode, MorseBit, MorseDecoder, MorseEncoder,
+    MorseSymbol, START_SEQUENCE, UnaryCode, parse_bits,
+};
+
+fn frame(text: &str) -> Vec<Bit> {
+    START_SEQUENCE
+        .into_iter()
+        .chain(MorseEncoder::from(text).map(Result::unwrap))
+        .collect()
+}
+
+// every event along with the state the decoder was left in
+fn events(decoder: &mut MorseDecoder, bits: &[Bit]) -> Vec<(DecoderEvent, DecoderState)> {
+    let mut seen = Vec::new();
+    for bit in bits {
+        for event in decoder.push(*bit).unwrap() {
+            seen.push((event, decoder.state()));
+        }
+    }
+    seen
+}
+
+#[test]
+fn frames_start_then_carry_symbols_then_end() {
+    use DecoderEvent::*;
+    use DecoderState::*;
+
+    let mut decoder = MorseDecoder::new();
+    assert_eq!(decoder.state(), WaitingForStart);
+    assert_eq!(
+        events(&mut decoder, &frame("HI 5")),
+        [
+            (FrameStart, ListeningForMessage),
+            (Symbol(MorseSymbol::Char('H')), ListeningForMessage),
+            (Symbol(MorseSymbol::Char('I')), ListeningForMessage),
+            (Symbol(MorseSymbol::Char(' ')), ListeningForMessage),
+            (Symbol(MorseSymbol::Char('5')), Processing),
+            (FrameEnd, Processing),
+        ]
+    );
+
+    // the next bit goes back to looking for a start
+    decoder.push(Bit::Lo).unwrap();
+    assert_eq!(decoder.state(), WaitingForStart);
+}
+
+#[test]
+fn the_last_char_and_the_end_come_from_one_bit() {
+    let mut decoder = MorseDecoder::new();
+    let bits = frame("E");
+    let (last, rest) = bits.split_last().unwrap();
+    events(&mut decoder, rest);
+    assert_eq!(
+        decoder.push(*last).unwrap()[..],
+        [
+            DecoderEvent::Symbol(MorseSymbol::Char('E')),
+            DecoderEvent::FrameEnd
+        ]
+    );
+}
+
+#[test]
+fn back_to_back_frames_decode_alike() {
+    let mut decoder = MorseDecoder::new();
+    let bits: Vec<Bit> = [frame("SOS"), frame("SOS")].concat();
+    let ends = events(&mut decoder, &bits)
+        .into_iter()
+        .filter(|(event, _)| *event == DecoderEvent::FrameEnd)
+        .count();
+    assert_eq!(ends, 2);
+}
+
+#[test]
+fn errors_send_it_back_to_waiting() {
+    let mut decoder = MorseDecoder::new();
+    events(&mut decoder, &START_SEQUENCE);
+    assert_eq!(decoder.state(), DecoderState::ListeningForMessage);
+
+    let bad = parse_bits::<BitSequece<6>>("HHHHHL").unwrap();
+    let e = bad.iter().find_map(|bit| decoder.push(*bit).err());
+    assert!(e.is_some());
+    assert_eq!(decoder.state(), DecoderState::WaitingForStart);
+
+    // nothing from the dropped frame leaks into the next one
+    let symbols: Vec<DecoderEvent> = events(&mut decoder, &frame("OK"))
+        .into_iter()
+        .map(|(event, _)| event)
+        .collect();
+    assert_eq!(
+        symbols,
+        [
+            DecoderEvent::FrameStart,
+            DecoderEvent::Symbol(MorseSymbol::Char('O')),
+            DecoderEvent::Symbol(MorseSymbol::Char('K')),
+            DecoderEvent::FrameEnd,
+        ]
+    );
+}
+
+#[test]
+fn reset_drops_a_frame_in_progress() {
+    let mut decoder = MorseDecoder::new();
+    let bits = frame("TEST");
+    events(&mut decoder, &bits[..START_SEQUENCE.len() + 5]);
+    decoder.reset();
+    assert_eq!(decoder.state(), DecoderState::WaitingForStart);
+
+    // what's left of the frame never matches the start sequence
+    assert!(events(&mut decoder, &bits[START_SEQUENCE.len() + 5..]).is_empty());
+    assert_eq!(events(&mut decoder, &frame("T")).len(), 3);
+}
+
+#[test]
+fn an_empty_message_still_ends_the_frame() {
+    let line_break: Vec<Bit> = UnaryCode::default().encode(MorseBit::LineBreak).collect();
+    let bits: Vec<Bit> = MorseEncoder::from("").map(Result::unwrap).collect();
+    assert_eq!(bits, line_break);
+
+    let mut decoder = MorseDecoder::new();
+    assert_eq!(
+        events(&mut decoder, &frame("")),
+        [
+            (DecoderEvent::FrameStart, DecoderState::ListeningForMessage),
+            (DecoderEvent::FrameEnd, DecoderState::Processing),
+        ]
+    );
+}
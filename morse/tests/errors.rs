@@ -0,0 +1,110 @@
+use morse::{
+    Bit, BitSequece, DECODE_TREE, ErrorKind, LineCode, LineDecoder, MorseBit, MorseDecoder,
+    MorseEncoder, MorseError, Notation, Offending, Position, START_SEQUENCE, UnaryCode, parse_bits,
+};
+
+/// a frame carrying "HE", cut off before its line break
+fn he() -> Vec<Bit> {
+    let mut bits = START_SEQUENCE.to_vec();
+    bits.extend(MorseEncoder::from("HE").map(Result::unwrap));
+    // the line break is three Hi and a Lo, swap it for a char break
+    bits.truncate(bits.len() - 4);
+    bits.extend(parse_bits::<BitSequece<2>>("HL").unwrap());
+    bits
+}
+
+fn push_all(decoder: &mut MorseDecoder, bits: &[Bit]) -> Result<(), MorseError> {
+    bits.iter().try_for_each(|bit| decoder.push(*bit).map(drop))
+}
+
+#[test]
+fn line_errors_say_where_and_what() {
+    let frame = he();
+    let mut decoder = MorseDecoder::new();
+    push_all(&mut decoder, &frame).unwrap();
+
+    // five Hi is no element of the compact code
+    let e = push_all(
+        &mut decoder,
+        &parse_bits::<BitSequece<6>>("HHHHHL").unwrap(),
+    )
+    .unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::UnknownBitSequence);
+    assert_eq!(
+        e.position(),
+        Some(Position {
+            bit: (frame.len() - START_SEQUENCE.len() + 6) as u64,
+            symbol: 2,
+        })
+    );
+    match e.sequence() {
+        Some(Offending::Bits(bits)) => assert_eq!(Notation(bits).to_string(), "HHHHHL"),
+        other => panic!("expected bits, got {other:?}"),
+    }
+    assert_eq!(e.partial(), "HE");
+    assert!(
+        e.to_string()
+            .ends_with(r#"symbol 2 reading HHHHHL after "HE""#),
+        "{e}"
+    );
+}
+
+#[test]
+fn the_next_frame_starts_clean() {
+    let mut decoder = MorseDecoder::new();
+    push_all(&mut decoder, &he()).unwrap();
+    push_all(
+        &mut decoder,
+        &parse_bits::<BitSequece<6>>("HHHHHL").unwrap(),
+    )
+    .unwrap_err();
+
+    push_all(&mut decoder, &START_SEQUENCE).unwrap();
+    let e = push_all(
+        &mut decoder,
+        &parse_bits::<BitSequece<6>>("HHHHHL").unwrap(),
+    )
+    .unwrap_err();
+    assert_eq!(e.position(), Some(Position { bit: 6, symbol: 0 }));
+    assert_eq!(e.partial(), "");
+}
+
+#[test]
+fn tree_errors_carry_the_elements() {
+    let e = DECODE_TREE.decode(&[MorseBit::Dot; 9]).unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::UnknownMorseSequence);
+    match e.sequence() {
+        Some(Offending::Elements(elements)) => assert_eq!(elements[..], [MorseBit::Dot; 9]),
+        other => panic!("expected elements, got {other:?}"),
+    }
+    assert_eq!(e.position(), None);
+}
+
+#[test]
+fn bare_kinds_print_on_their_own() {
+    let e = MorseError::from(ErrorKind::ChecksumMismatch {
+        expected: 0xbeef,
+        actual: 0x1234,
+    });
+    assert_eq!(e.to_string(), "checksum mismatch, expected beef got 1234");
+    assert_eq!(e.partial(), "");
+}
+
+#[test]
+fn endless_runs_stop_at_the_buffer() {
+    let mut decoder = UnaryCode::COMPACT.decoder();
+    let e = parse_bits::<BitSequece<12>>("HHHHHHHHHHHH")
+        .unwrap()
+        .into_iter()
+        .map(|bit| decoder.push(bit))
+        .find_map(Result::err)
+        .unwrap();
+    assert_eq!(e.kind(), ErrorKind::FullBuffer);
+    match e.sequence() {
+        Some(Offending::Bits(bits)) => assert_eq!(Notation(bits).to_string(), "HHHHHHHH"),
+        other => panic!("expected bits, got {other:?}"),
+    }
+
+    decoder.reset();
+    assert_eq!(decoder.push(Bit::Lo).unwrap(), Some(MorseBit::Dot));
+}
@@ -0,0 +1,295 @@
+use morse::{
+    Bit, ByteDecoder, ByteEncoder, ByteEvent, Checksum, ErrorKind, Fec, MAX_PARITY, MorseError,
+    Parity, ReedSolomon, START_SEQUENCE, hamming_decode, hamming_encode,
+};
+
+const MSG: &[u8] = b"UCSC CSE 121 ABCDEFGHIJKLM NOPQRSTUVWXYZ 12345 67890";
+
+// line bits per uart byte, start and stop included
+const FRAME_BITS: usize = 10;
+
+// uart bytes after the encoded ones with fec on, the end mark and two breaks
+const CLOSE_FRAMES: usize = 3;
+
+/// xorshift, so the error patterns are random but the same every run
+struct Rng(u32);
+
+impl Rng {
+    fn next(&mut self) -> u32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        self.next() as usize % n
+    }
+
+    /// `count` different indices below `n`
+    fn distinct(&mut self, count: usize, n: usize) -> Vec<usize> {
+        let mut picked = Vec::new();
+        while picked.len() < count {
+            let i = self.below(n);
+            if !picked.contains(&i) {
+                picked.push(i);
+            }
+        }
+        picked
+    }
+}
+
+fn flip(bit: Bit) -> Bit {
+    match bit {
+        Bit::Hi => Bit::Lo,
+        Bit::Lo => Bit::Hi,
+    }
+}
+
+fn payload(rng: &mut Rng, len: usize) -> Vec<u8> {
+    (0..len).map(|_| rng.next() as u8).collect()
+}
+
+/// the line bits after the start sequence, for `bytes` sent with `fec`
+fn frame(bytes: &[u8], fec: Fec) -> Vec<Bit> {
+    ByteEncoder::new(bytes.iter().copied())
+        .with_checksum(Checksum::Crc16)
+        .with_fec(fec)
+        .collect()
+}
+
+fn receive(bits: &[Bit], fec: Fec) -> Result<Vec<u8>, MorseError> {
+    let mut decoder = ByteDecoder::<heapless::Vec<u8, 1024>>::new()
+        .with_checksum(Checksum::Crc16)
+        .with_fec(fec);
+
+    for bit in START_SEQUENCE.iter().chain(bits) {
+        if let Some(ByteEvent::FrameEnd) = decoder.push(*bit)? {
+            return Ok(decoder.payload().to_vec());
+        }
+    }
+    panic!("the frame never ended");
+}
+
+/// flips one of the data bits of uart byte `byte`, leaving the framing alone
+fn flip_data_bit(bits: &mut [Bit], byte: usize, rng: &mut Rng) {
+    let i = byte * FRAME_BITS + 1 + rng.below(8);
+    bits[i] = flip(bits[i]);
+}
+
+#[test]
+fn hamming_fixes_any_single_flip() {
+    for nibble in 0..16 {
+        let code = hamming_encode(nibble);
+        assert_eq!(code & 0x80, 0);
+        assert_eq!(hamming_decode(code), nibble);
+
+        for bit in 0..8 {
+            assert_eq!(hamming_decode(code ^ 1 << bit), nibble, "bit {bit}");
+        }
+    }
+}
+
+#[test]
+fn hamming_codewords_are_three_apart() {
+    for a in 0..16 {
+        for b in 0..a {
+            let distance = (hamming_encode(a) ^ hamming_encode(b)).count_ones();
+            assert!(distance >= 3, "{a} and {b} are {distance} apart");
+        }
+    }
+}
+
+#[test]
+fn reed_solomon_fixes_up_to_half_its_parity() {
+    let mut rng = Rng(0x1234_5678);
+
+    for parity in [2, 8, 16, 32] {
+        let code = ReedSolomon::new(parity);
+        for len in [1, 10, 100, code.data_len()] {
+            let data = payload(&mut rng, len);
+            let mut block = data.clone();
+            block.resize(len + parity, 0);
+            let (data_part, parity_part) = block.split_at_mut(len);
+            code.encode(data_part, parity_part);
+
+            let mut clean = block.clone();
+            assert_eq!(code.decode(&mut clean).unwrap(), 0);
+
+            for errors in 1..=parity / 2 {
+                let mut corrupted = block.clone();
+                for i in rng.distinct(errors, corrupted.len()) {
+                    corrupted[i] ^= 1 + rng.below(255) as u8;
+                }
+
+                assert_eq!(code.decode(&mut corrupted).unwrap(), errors);
+                assert_eq!(corrupted, block, "parity {parity}, len {len}");
+            }
+        }
+    }
+}
+
+#[test]
+fn reed_solomon_refuses_blocks_past_saving() {
+    let mut rng = Rng(0xdead_beef);
+    let code = ReedSolomon::new(8);
+    let data = payload(&mut rng, 40);
+    let mut block = data.clone();
+    block.resize(48, 0);
+    let (data_part, parity_part) = block.split_at_mut(40);
+    code.encode(data_part, parity_part);
+
+    let mut refused = 0;
+    for _ in 0..200 {
+        let mut corrupted = block.clone();
+        for i in rng.distinct(5, corrupted.len()) {
+            corrupted[i] ^= 1 + rng.below(255) as u8;
+        }
+        match code.decode(&mut corrupted).map_err(|e| e.kind()) {
+            Err(ErrorKind::TooManyErrors) => refused += 1,
+            // landing on some other codeword is possible, but never this one
+            Ok(_) => assert_ne!(corrupted, block),
+            Err(e) => panic!("unexpected {e:?}"),
+        }
+    }
+    assert!(refused > 190, "only {refused} of 200 refused");
+}
+
+#[test]
+fn only_parities_reed_solomon_can_do_are_built() {
+    for parity in [0, MAX_PARITY as u8 + 1, u8::MAX] {
+        assert_eq!(
+            Fec::reed_solomon(parity).unwrap_err().kind(),
+            ErrorKind::UnsupportedParity(parity)
+        );
+    }
+    for parity in [1, 8, MAX_PARITY as u8] {
+        let Fec::ReedSolomon { parity: built } = Fec::reed_solomon(parity).unwrap() else {
+            panic!("{parity} didn't give reed-solomon");
+        };
+        assert_eq!(built.get(), parity);
+        assert_eq!(Parity::new(parity).unwrap(), built);
+    }
+}
+
+#[test]
+fn clean_frames_round_trip() {
+    let mut rng = Rng(42);
+    for fec in [
+        Fec::None,
+        Fec::Hamming74,
+        Fec::reed_solomon(8).unwrap(),
+        Fec::reed_solomon(32).unwrap(),
+    ] {
+        for data in [Vec::new(), MSG.to_vec(), payload(&mut rng, 500)] {
+            let bits = frame(&data, fec);
+            let close = if fec == Fec::None { 1 } else { CLOSE_FRAMES };
+            let bytes = bits.len() / FRAME_BITS - close;
+            assert_eq!(bytes, fec.encoded_len(data.len() + 2), "{fec:?}");
+            assert_eq!(receive(&bits, fec).unwrap(), data, "{fec:?}");
+        }
+    }
+}
+
+#[test]
+fn hamming_link_survives_a_flip_in_every_byte() {
+    let mut rng = Rng(7);
+    let clean = frame(MSG, Fec::Hamming74);
+
+    for _ in 0..50 {
+        let mut bits = clean.clone();
+        for byte in 0..bits.len() / FRAME_BITS - CLOSE_FRAMES {
+            flip_data_bit(&mut bits, byte, &mut rng);
+        }
+        assert_eq!(receive(&bits, Fec::Hamming74).unwrap(), MSG);
+    }
+}
+
+#[test]
+fn reed_solomon_link_survives_scattered_flips() {
+    let mut rng = Rng(99);
+    let fec = Fec::reed_solomon(16).unwrap();
+    // long enough to need three blocks, each of which gets its share
+    let data = payload(&mut rng, 600);
+    let clean = frame(&data, fec);
+    let blocks = [
+        0..255,
+        255..510,
+        510..clean.len() / FRAME_BITS - CLOSE_FRAMES,
+    ];
+
+    for _ in 0..20 {
+        let mut bits = clean.clone();
+        for block in blocks.clone() {
+            for byte in rng.distinct(8, block.len()) {
+                flip_data_bit(&mut bits, block.start + byte, &mut rng);
+            }
+        }
+        assert_eq!(receive(&bits, fec).unwrap(), data);
+    }
+}
+
+#[test]
+fn reed_solomon_link_survives_lost_stop_bits() {
+    let fec = Fec::reed_solomon(8).unwrap();
+    let mut bits = frame(MSG, fec);
+    for byte in [3, 20, 41] {
+        let stop = byte * FRAME_BITS + FRAME_BITS - 1;
+        bits[stop] = flip(bits[stop]);
+    }
+    assert_eq!(receive(&bits, fec).unwrap(), MSG);
+}
+
+#[test]
+fn without_fec_a_single_flip_is_caught_not_fixed() {
+    let mut rng = Rng(3);
+    let mut bits = frame(MSG, Fec::None);
+    flip_data_bit(&mut bits, 10, &mut rng);
+
+    assert!(matches!(
+        receive(&bits, Fec::None).map_err(|e| e.kind()),
+        Err(ErrorKind::ChecksumMismatch { .. })
+    ));
+}
+
+#[test]
+fn too_many_flips_are_never_accepted() {
+    let mut rng = Rng(11);
+    let fec = Fec::reed_solomon(4).unwrap();
+    let clean = frame(MSG, fec);
+
+    for _ in 0..100 {
+        let mut bits = clean.clone();
+        for byte in rng.distinct(6, clean.len() / FRAME_BITS - CLOSE_FRAMES) {
+            flip_data_bit(&mut bits, byte, &mut rng);
+        }
+        match receive(&bits, fec).map_err(|e| e.kind()) {
+            Err(ErrorKind::TooManyErrors | ErrorKind::ChecksumMismatch { .. }) => {}
+            other => panic!("accepted a ruined frame: {other:?}"),
+        }
+    }
+}
+
+#[test]
+fn a_zero_that_lost_its_stop_bit_is_no_break() {
+    // every codeword of an all zero payload is 0, so each one looks like a
+    // break once its stop bit flips, the last one included
+    let data = [0; 8];
+    let clean: Vec<Bit> = ByteEncoder::new(data).with_fec(Fec::Hamming74).collect();
+    let encoded = clean.len() / FRAME_BITS - CLOSE_FRAMES;
+
+    for byte in 0..encoded {
+        let mut bits = clean.clone();
+        let stop = byte * FRAME_BITS + FRAME_BITS - 1;
+        bits[stop] = flip(bits[stop]);
+
+        let mut decoder = ByteDecoder::<heapless::Vec<u8, 64>>::new().with_fec(Fec::Hamming74);
+        let end = START_SEQUENCE
+            .iter()
+            .chain(&bits)
+            .filter_map(|bit| decoder.push(*bit).unwrap())
+            .find(|event| *event == ByteEvent::FrameEnd);
+        assert!(end.is_some(), "byte {byte}");
+        assert_eq!(decoder.payload(), data, "byte {byte}");
+    }
+}
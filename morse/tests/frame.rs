@@ -0,0 +1,111 @@
+use morse::{
+    Bit, ByteEncoder, ErrorKind, FRAME_CHECKSUM, FrameDecoder, FrameEncoder, FrameEvent,
+    FrameHeader, HEADER_LEN, MessageType, MorseError, PROTOCOL_VERSION, START_SEQUENCE,
+};
+
+fn receive(bits: impl IntoIterator<Item = Bit>) -> Result<(FrameHeader, Vec<u8>), MorseError> {
+    let mut decoder = FrameDecoder::<heapless::Vec<u8, 256>>::new();
+    for bit in START_SEQUENCE.into_iter().chain(bits) {
+        if let Some(FrameEvent::FrameEnd(header)) = decoder.push(bit)? {
+            return Ok((header, decoder.payload().to_vec()));
+        }
+    }
+    panic!("the frame never ended");
+}
+
+/// `bytes` framed as is, with a good checksum, so only the layout is wrong
+fn raw(bytes: &[u8]) -> impl Iterator<Item = Bit> {
+    ByteEncoder::new(bytes.to_vec()).with_checksum(FRAME_CHECKSUM)
+}
+
+#[test]
+fn headers_round_trip_through_bytes() {
+    for message_type in [
+        MessageType::Text,
+        MessageType::Data,
+        MessageType::Ack,
+        MessageType::Ping,
+    ] {
+        let mut header = FrameHeader::new(message_type, 0xbeef, 0x0102);
+        header.flags = 0xa;
+        let bytes = header.to_bytes();
+        assert_eq!(
+            bytes,
+            [
+                PROTOCOL_VERSION,
+                0xa0 | message_type as u8,
+                0xbe,
+                0xef,
+                1,
+                2
+            ]
+        );
+        assert_eq!(FrameHeader::from_bytes(&bytes).unwrap(), header);
+    }
+
+    // only the low nibble of the flags fits
+    let mut header = FrameHeader::new(MessageType::Data, 1, 0);
+    header.flags = 0x31;
+    assert_eq!(
+        FrameHeader::from_bytes(&header.to_bytes()).unwrap().flags,
+        1
+    );
+}
+
+#[test]
+fn frames_round_trip() {
+    let msg = b"the reading is 1200";
+    let frame = FrameEncoder::new(MessageType::Data, 7, msg).unwrap();
+    let (header, payload) = receive(frame).unwrap();
+    assert_eq!(
+        header,
+        FrameHeader::new(MessageType::Data, 7, msg.len() as u16)
+    );
+    assert_eq!(payload, msg);
+}
+
+#[test]
+fn other_versions_are_refused() {
+    let mut bytes = FrameHeader::new(MessageType::Text, 1, 2).to_bytes();
+    bytes[0] = PROTOCOL_VERSION + 1;
+    let frame = [&bytes[..], b"hi"].concat();
+
+    let e = receive(raw(&frame)).unwrap_err();
+    assert_eq!(
+        e.kind(),
+        ErrorKind::UnsupportedVersion(PROTOCOL_VERSION + 1)
+    );
+}
+
+#[test]
+fn unknown_types_are_malformed() {
+    let mut bytes = FrameHeader::new(MessageType::Text, 1, 0).to_bytes();
+    bytes[1] = 0x0f;
+    assert_eq!(
+        FrameHeader::from_bytes(&bytes).unwrap_err().kind(),
+        ErrorKind::MalformedFrame
+    );
+    assert_eq!(
+        receive(raw(&bytes)).unwrap_err().kind(),
+        ErrorKind::MalformedFrame
+    );
+}
+
+#[test]
+fn lengths_have_to_match() {
+    let header = FrameHeader::new(MessageType::Text, 1, 5).to_bytes();
+    for payload in [&b"hi"[..], b"too long"] {
+        let frame = [&header[..], payload].concat();
+        assert_eq!(
+            receive(raw(&frame)).unwrap_err().kind(),
+            ErrorKind::MalformedFrame,
+            "{payload:?}"
+        );
+    }
+
+    // not even a whole header
+    assert_eq!(
+        receive(raw(&header[..HEADER_LEN - 1])).unwrap_err().kind(),
+        ErrorKind::MalformedFrame
+    );
+}
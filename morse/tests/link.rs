@@ -0,0 +1,91 @@
+use morse::{Biphase, Bit, Checksum, Fec, LinkConfig, Preamble, TAIL_BITS, parse_bits};
+
+#[test]
+fn samples_per_step_rounds_to_the_nearest() {
+    assert_eq!(LinkConfig::COMPACT.samples_per_step(), 1);
+    // biphase levels are half a step
+    assert_eq!(LinkConfig::MANCHESTER.samples_per_step(), 4);
+    assert_eq!(LinkConfig::ITU.samples_per_step(), 4999);
+
+    let link = |time_step_micros, sample_hertz| LinkConfig {
+        time_step_micros,
+        sample_hertz,
+        ..LinkConfig::COMPACT
+    };
+    assert_eq!(link(3, 500_000).samples_per_step(), 2);
+    assert_eq!(link(3, 499_999).samples_per_step(), 1);
+    assert_eq!(link(10, 250_000).samples_per_step(), 3);
+    assert_eq!(link(10, 249_999).samples_per_step(), 2);
+}
+
+#[test]
+fn too_slow_a_sampler_still_reads_every_step() {
+    let link = LinkConfig {
+        sample_hertz: 1000,
+        ..LinkConfig::COMPACT
+    };
+    assert_eq!(link.samples_per_step(), 1);
+}
+
+#[test]
+fn oversampling_gets_as_many_readings_as_asked() {
+    for preset in [
+        LinkConfig::COMPACT,
+        LinkConfig::HARDENED,
+        LinkConfig::MANCHESTER,
+        LinkConfig::ITU,
+        LinkConfig::FRAMES,
+    ] {
+        for samples in 1..=16 {
+            let link = preset.with_oversampling(samples);
+            assert_eq!(link.samples_per_step(), samples, "{preset:?}");
+            assert_eq!(link.time_step_micros, preset.time_step_micros);
+        }
+    }
+
+    // a biphase line needs twice the rate for the same readings per level
+    let plain = LinkConfig {
+        time_step_micros: 100,
+        ..LinkConfig::COMPACT
+    };
+    let manchester = LinkConfig {
+        biphase: Some(Biphase::Manchester),
+        ..plain
+    };
+    assert_eq!(plain.with_oversampling(4).sample_hertz, 40_000);
+    assert_eq!(manchester.with_oversampling(4).sample_hertz, 80_000);
+}
+
+#[test]
+fn compact_keys_what_tx_sent_before_presets() {
+    let link = LinkConfig::COMPACT;
+    assert_eq!(link.preamble, Preamble::LEGACY);
+    assert_eq!(link.checksum, Checksum::None);
+    assert_eq!(link.fec, Fec::None);
+
+    // the start sequence, then E, a char break, T and the line break
+    let mut expected: heapless::Vec<Bit, 64> = parse_bits("HHHHHLLLHH  L HL HHL HHHL").unwrap();
+    expected.extend([Bit::Lo; TAIL_BITS]);
+    let sent: Vec<Bit> = link.transmission("et", 0).unwrap().collect();
+    assert_eq!(sent, expected[..]);
+}
+
+#[test]
+fn hardened_only_adds_protection() {
+    let hardened = LinkConfig::HARDENED;
+    assert_eq!(hardened.preamble, Preamble::BARKER_13);
+    assert_eq!(hardened.checksum, Checksum::Crc16);
+    assert_eq!(hardened.fec, Fec::reed_solomon(8).unwrap());
+    assert_eq!(
+        LinkConfig {
+            preamble: Preamble::LEGACY,
+            checksum: Checksum::None,
+            fec: Fec::None,
+            ..hardened
+        },
+        LinkConfig::COMPACT
+    );
+
+    // humans copying the LED don't get a trailer
+    assert_eq!(LinkConfig::ITU.checksum, Checksum::None);
+}
@@ -0,0 +1,66 @@
+use morse::{
+    Bit, BitSequece, CHAR_SEQUENCE_LEN, LineCode, LineDecoder, MorseBit, MorseBitSequence,
+    MorseConversion, MorseEncoder, Notation, Prosign, UnaryCode, parse_bits, parse_morse,
+};
+
+/// the elements the encoder would send for `msg`, through the line code and
+/// back
+fn elements(msg: &str) -> Vec<MorseBit> {
+    let bits: Vec<Bit> = MorseEncoder::from(msg).map(Result::unwrap).collect();
+    let mut decoder = UnaryCode::COMPACT.decoder();
+    bits.into_iter()
+        .filter_map(|bit| decoder.push(bit).unwrap())
+        .collect()
+}
+
+#[test]
+fn encoder_output_round_trips_through_notation() {
+    for msg in ["A B", "SOS", "HELLO WORLD 73", "A  B", "E"] {
+        let elements = elements(msg);
+        let text = Notation(&elements).to_string();
+        assert_eq!(
+            parse_morse::<MorseBitSequence>(&text).unwrap(),
+            elements[..],
+            "{text:?}"
+        );
+    }
+}
+
+#[test]
+fn hand_written_vectors_match_the_encoder() {
+    assert_eq!(Notation(&elements("A B")).to_string(), ".- / -...\n");
+    assert_eq!(
+        parse_morse::<MorseBitSequence>(".- / -...\n").unwrap(),
+        elements("A B")[..]
+    );
+    // three spaces is the other common way to split words
+    assert_eq!(
+        parse_morse::<MorseBitSequence>(".-   -...\n").unwrap(),
+        elements("A B")[..]
+    );
+    assert_eq!(
+        parse_morse::<MorseBitSequence>("... --- ...\n").unwrap(),
+        elements("SOS")[..]
+    );
+}
+
+#[test]
+fn prosigns_print_as_one_run() {
+    let seq: MorseBitSequence<CHAR_SEQUENCE_LEN> = Prosign::SK.to_morse_bit_sequence().unwrap();
+    assert_eq!(Notation(&seq).to_string(), "...-.-");
+    assert_eq!(parse_morse::<MorseBitSequence>("...-.-").unwrap(), seq);
+}
+
+#[test]
+fn line_bits_round_trip() {
+    let bits = parse_bits::<BitSequece<16>>("HHHH_HLLL hh").unwrap();
+    assert_eq!(Notation(&bits).to_string(), "HHHHHLLLHH");
+    assert_eq!(&bits[..], &morse::START_SEQUENCE[..]);
+    assert!(parse_bits::<BitSequece<4>>("HHLLH").is_err());
+}
+
+#[test]
+fn garbage_is_rejected() {
+    assert!(parse_morse::<MorseBitSequence>(".- x").is_err());
+    assert!(parse_bits::<BitSequece<8>>("HLX").is_err());
+}
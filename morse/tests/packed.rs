@@ -0,0 +1,95 @@
+use morse::{
+    Bit, BitSequece, ErrorKind, LinkConfig, MorseEncoder, PackedBits, Preamble, START_SEQUENCE,
+    StartDetector, parse_bits,
+};
+
+fn pack<const W: usize>(bits: impl IntoIterator<Item = Bit>) -> PackedBits<W> {
+    let bits: Vec<Bit> = bits.into_iter().collect();
+    PackedBits::try_from(&bits[..]).unwrap()
+}
+
+#[test]
+fn bits_come_back_out_in_order() {
+    let bits: Vec<Bit> = MorseEncoder::from("HELLO WORLD")
+        .map(Result::unwrap)
+        .collect();
+    let mut packed = pack::<4>(bits.iter().copied());
+
+    assert_eq!(packed.len(), bits.len());
+    assert_eq!(packed, bits[..]);
+    assert_eq!(packed.iter().len(), bits.len());
+    assert_eq!(packed.get(bits.len()), None);
+
+    packed.truncate(40);
+    assert_eq!(packed, bits[..40]);
+    // what's left over is cleared, so equal bits are equal buffers
+    assert_eq!(packed, pack::<4>(bits[..40].iter().copied()));
+}
+
+#[test]
+fn full_buffers_say_so() {
+    let mut packed = pack::<1>([Bit::Hi; 32]);
+    assert!(packed.is_full());
+    assert_eq!(
+        packed.push(Bit::Lo).unwrap_err().kind(),
+        ErrorKind::FullBuffer
+    );
+}
+
+#[test]
+fn words_read_across_the_boundary() {
+    let bits = parse_bits::<BitSequece<64>>(&"HHLL".repeat(16)).unwrap();
+    let packed = PackedBits::<2>::from_slice(&bits);
+    assert_eq!(packed.word(0, 4), Some(0b1100));
+    assert_eq!(packed.word(30, 4), Some(0b0011));
+    assert_eq!(packed.word(2, 32), Some(0x3333_3333));
+    assert_eq!(packed.word(33, 32), None);
+}
+
+#[test]
+fn too_many_bits_dont_pack() {
+    let bits = [Bit::Lo; 33];
+    assert_eq!(
+        PackedBits::<1>::try_from(&bits[..]).unwrap_err().kind(),
+        ErrorKind::FullBuffer
+    );
+}
+
+#[test]
+fn the_register_matches_where_the_window_did() {
+    for preamble in [
+        Preamble::LEGACY,
+        Preamble::BARKER_13,
+        Preamble::M_SEQUENCE_15,
+    ] {
+        let line: Vec<Bit> = parse_bits::<BitSequece<20>>("LHLHHLLHLLLHHHLHLHHL")
+            .unwrap()
+            .into_iter()
+            .chain(preamble.packed())
+            .chain(START_SEQUENCE)
+            .collect();
+
+        let mut detector = StartDetector::new(preamble);
+        let found = line.iter().find_map(|bit| detector.push(*bit)).unwrap();
+        // the first window close enough, compared a bit at a time
+        let expected = line
+            .windows(preamble.bits().len())
+            .position(|window| {
+                let errors = window.iter().zip(preamble.bits()).filter(|(a, b)| a != b);
+                errors.count() <= preamble.max_errors()
+            })
+            .unwrap();
+        assert_eq!(found.position, expected as u64, "{preamble:?}");
+    }
+}
+
+#[test]
+fn transmissions_start_with_the_packed_preamble() {
+    let link = LinkConfig::HARDENED;
+    let sent = pack::<32>(link.transmission("73", 0).unwrap());
+    let preamble = link.preamble.packed();
+    assert_eq!(
+        sent.word(0, preamble.len()),
+        preamble.word(0, preamble.len())
+    );
+}
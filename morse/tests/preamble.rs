@@ -0,0 +1,117 @@
+use morse::{
+    Bit, BitSequece, DecoderEvent, LinkConfig, MorseDecoder, MorseEncoder, Preamble, PreambleMatch,
+    StartDetector, parse_bits,
+};
+
+fn flip(bit: Bit) -> Bit {
+    match bit {
+        Bit::Hi => Bit::Lo,
+        Bit::Lo => Bit::Hi,
+    }
+}
+
+/// `preamble` after a bit of idle line, with the bits at `flipped` wrong
+fn line(preamble: Preamble, flipped: &[usize]) -> Vec<Bit> {
+    let mut bits = preamble.bits().to_vec();
+    for i in flipped {
+        bits[*i] = flip(bits[*i]);
+    }
+    [Bit::Lo; 5].into_iter().chain(bits).collect()
+}
+
+fn detect(preamble: Preamble, bits: &[Bit]) -> Option<PreambleMatch> {
+    let mut detector = StartDetector::new(preamble);
+    bits.iter().find_map(|bit| detector.push(*bit))
+}
+
+#[test]
+fn clean_preambles_match_where_they_start() {
+    for preamble in [
+        Preamble::LEGACY,
+        Preamble::BARKER_13,
+        Preamble::M_SEQUENCE_15,
+    ] {
+        let found = detect(preamble, &line(preamble, &[])).unwrap();
+        assert_eq!(
+            found,
+            PreambleMatch {
+                position: 5,
+                errors: 0,
+                len: preamble.len(),
+            }
+        );
+        assert_eq!(found.correlation(), preamble.len() as isize);
+    }
+}
+
+#[test]
+fn flips_are_forgiven_up_to_the_threshold() {
+    let preamble = Preamble::BARKER_13;
+    let found = detect(preamble, &line(preamble, &[0, 7])).unwrap();
+    assert_eq!((found.position, found.errors), (5, 2));
+    assert_eq!(found.correlation(), 9);
+
+    assert_eq!(detect(preamble, &line(preamble, &[0, 7, 11])), None);
+    // the old start sequence takes no errors at all
+    assert_eq!(
+        detect(Preamble::LEGACY, &line(Preamble::LEGACY, &[3])),
+        None
+    );
+}
+
+#[test]
+fn the_same_bits_never_match_twice() {
+    let preamble = Preamble::BARKER_13;
+    let mut detector = StartDetector::new(preamble);
+    let bits = line(preamble, &[]);
+    let matches = bits
+        .iter()
+        .chain(&[Bit::Lo; 4])
+        .filter_map(|bit| detector.push(*bit))
+        .count();
+    assert_eq!(matches, 1);
+    assert_eq!(detector.last_match().map(|found| found.position), Some(5));
+}
+
+#[test]
+fn reset_forgets_a_partial_preamble() {
+    let preamble = Preamble::LEGACY;
+    let mut detector = StartDetector::new(preamble);
+    let (head, tail) = preamble.bits().split_at(6);
+    head.iter()
+        .for_each(|bit| assert!(detector.push(*bit).is_none()));
+    detector.reset();
+    assert!(tail.iter().all(|bit| detector.push(*bit).is_none()));
+}
+
+#[test]
+fn positions_count_the_frames_in_between() {
+    let config = LinkConfig {
+        preamble: Preamble::BARKER_13,
+        ..LinkConfig::COMPACT
+    };
+    let frame = |text| -> Vec<Bit> {
+        config
+            .preamble
+            .bits()
+            .iter()
+            .copied()
+            .chain(MorseEncoder::with_config(text, &config).map(Result::unwrap))
+            .collect()
+    };
+    let gap = parse_bits::<BitSequece<3>>("LLL").unwrap();
+    let first = frame("HI".chars());
+    let line: Vec<Bit> = [&first[..], &gap, &frame("OK".chars())].concat();
+
+    let mut decoder = MorseDecoder::with_config(&config);
+    let starts: Vec<u64> = line
+        .iter()
+        .filter_map(|bit| {
+            let events = decoder.push(*bit).unwrap();
+            events
+                .contains(&DecoderEvent::FrameStart)
+                .then(|| decoder.preamble_match().unwrap().position)
+        })
+        .collect();
+    assert_eq!(starts, [0, (first.len() + gap.len()) as u64]);
+}
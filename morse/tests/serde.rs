@@ -0,0 +1,124 @@
+#![cfg(feature = "serde")]
+
+use morse::{
+    Alphabet, AnyLineCode, BitSequece, CHAR_SEQUENCE_LEN, ENGLISH, Fec, ItuCode, LinkConfig,
+    MorseBit, MorseBitSequence, MorseConversion, MorseDecoder, MorseError, PackedBits, Preamble,
+    START_SEQUENCE, UnaryCode, parse_bits,
+};
+use serde::{Serialize, de::DeserializeOwned};
+
+fn round_trip<T: Serialize + DeserializeOwned + PartialEq + core::fmt::Debug>(value: &T) {
+    let json = serde_json::to_string(value).unwrap();
+    assert_eq!(&serde_json::from_str::<T>(&json).unwrap(), value, "{json}");
+}
+
+#[test]
+fn sequences_dump_as_lists() {
+    let bits: BitSequece = parse_bits("HHHL").unwrap();
+    assert_eq!(
+        serde_json::to_string(&bits).unwrap(),
+        r#"["Hi","Hi","Hi","Lo"]"#
+    );
+    round_trip(&bits);
+
+    // packed ones too, they look no different
+    let packed = PackedBits::<1>::from_slice(&bits);
+    assert_eq!(
+        serde_json::to_string(&packed).unwrap(),
+        serde_json::to_string(&bits).unwrap()
+    );
+    round_trip(&packed);
+
+    let elements: MorseBitSequence<CHAR_SEQUENCE_LEN> = 'Q'.to_morse_bit_sequence().unwrap();
+    assert_eq!(elements[0], MorseBit::Dash);
+    round_trip(&elements);
+}
+
+#[test]
+fn errors_keep_their_context() {
+    let mut decoder = MorseDecoder::new();
+    let e = START_SEQUENCE
+        .into_iter()
+        // five Hi is no element of the compact code
+        .chain(parse_bits::<BitSequece<6>>("HHHHHL").unwrap())
+        .map(|bit| decoder.push(bit))
+        .find_map(Result::err)
+        .unwrap();
+    assert!(e.sequence().is_some());
+
+    let json = serde_json::to_string(&e).unwrap();
+    let back: MorseError = serde_json::from_str(&json).unwrap();
+    assert_eq!(back, e);
+    assert_eq!(back.to_string(), e.to_string());
+}
+
+#[test]
+fn configs_round_trip() {
+    for config in [
+        LinkConfig::COMPACT,
+        LinkConfig::HARDENED,
+        LinkConfig::ITU,
+        LinkConfig::MANCHESTER,
+        LinkConfig::FRAMES,
+    ] {
+        round_trip(&config);
+    }
+}
+
+#[test]
+fn statics_only_come_back_if_we_know_them() {
+    let made_up = r#"{"bits":["Hi","Lo","Hi"],"max_errors":0}"#;
+    assert!(serde_json::from_str::<Preamble>(made_up).is_err());
+
+    assert!(serde_json::to_string(&Alphabet::Custom(&ENGLISH)).is_err());
+}
+
+#[test]
+fn configs_that_new_would_refuse_are_refused() {
+    let json = serde_json::to_string(&UnaryCode::COMPACT).unwrap();
+    assert_eq!(json, r#"{"runs":[0,2,1,4,3]}"#);
+    for runs in ["[1,1,1,1,1]", "[0,2,1,4,200]"] {
+        let json = json.replace("[0,2,1,4,3]", runs);
+        assert!(serde_json::from_str::<UnaryCode>(&json).is_err(), "{json}");
+    }
+
+    round_trip(&ItuCode::farnsworth(18, 5));
+    for gaps in [
+        r#"{"char_wpm":0,"letter_gap":3,"word_gap":7}"#,
+        r#"{"char_wpm":20,"letter_gap":0,"word_gap":7}"#,
+        r#"{"char_wpm":20,"letter_gap":3,"word_gap":4}"#,
+        r#"{"char_wpm":20,"letter_gap":3,"word_gap":4000000000}"#,
+    ] {
+        assert!(serde_json::from_str::<ItuCode>(gaps).is_err(), "{gaps}");
+    }
+
+    round_trip(&Fec::reed_solomon(32).unwrap());
+    for parity in [0, 33] {
+        let json = format!(r#"{{"ReedSolomon":{{"parity":{parity}}}}}"#);
+        assert!(serde_json::from_str::<Fec>(&json).is_err(), "{json}");
+    }
+}
+
+#[test]
+fn whole_configs_are_checked_too() {
+    let config = LinkConfig {
+        fec: Fec::reed_solomon(4).unwrap(),
+        ..LinkConfig::FRAMES
+    };
+    let json = serde_json::to_string(&config).unwrap();
+    round_trip(&config);
+
+    // it would deserialize and then panic on the first transmission
+    let bad_fec = json.replace(r#""parity":4"#, r#""parity":0"#);
+    assert_ne!(bad_fec, json);
+    assert!(serde_json::from_str::<LinkConfig>(&bad_fec).is_err());
+
+    let config = LinkConfig {
+        line_code: AnyLineCode::Unary(UnaryCode::DASH_FIRST),
+        ..config
+    };
+    let json = serde_json::to_string(&config).unwrap();
+    let bad_code = json.replace("[0,1,2,4,3]", "[1,1,1,1,1]");
+    assert_ne!(bad_code, json);
+    assert!(serde_json::from_str::<LinkConfig>(&bad_code).is_err());
+}
@@ -0,0 +1,133 @@
+use morse::{
+    Bit, DecoderEvent, LineCode, LineDecoder, MorseBit, MorseDecoder, MorseEncoder, MorseError,
+    MorseSymbol, START_SEQUENCE, Soft, UnaryCode,
+};
+
+/// a frame for `msg` at full confidence, with the line idling Lo after it
+fn frame(msg: &str) -> Vec<Soft> {
+    START_SEQUENCE
+        .iter()
+        .copied()
+        .chain(MorseEncoder::from(msg).map(Result::unwrap))
+        .chain([Bit::Lo; 16])
+        .map(|bit| match bit {
+            Bit::Hi => Soft(100),
+            Bit::Lo => Soft(-100),
+        })
+        .collect()
+}
+
+fn receive(
+    samples: &[Soft],
+    push: impl Fn(&mut MorseDecoder, Soft) -> Result<Vec<DecoderEvent>, MorseError>,
+) -> Result<String, MorseError> {
+    let mut decoder = MorseDecoder::new();
+    let mut text = String::new();
+    for sample in samples {
+        for event in push(&mut decoder, *sample)? {
+            match event {
+                DecoderEvent::Symbol(MorseSymbol::Char(c)) => text.push(c),
+                DecoderEvent::FrameEnd => return Ok(text),
+                _ => {}
+            }
+        }
+    }
+    panic!("the frame never ended, got {text:?}");
+}
+
+fn soft(decoder: &mut MorseDecoder, sample: Soft) -> Result<Vec<DecoderEvent>, MorseError> {
+    decoder.push_soft(sample).map(|events| events.to_vec())
+}
+
+fn hard(decoder: &mut MorseDecoder, sample: Soft) -> Result<Vec<DecoderEvent>, MorseError> {
+    decoder.push(sample.hard()).map(|events| events.to_vec())
+}
+
+#[test]
+fn clean_frames_decode_the_same() {
+    for msg in ["SOS", "HELLO WORLD 73", "A B"] {
+        let samples = frame(msg);
+        assert_eq!(receive(&samples, soft).unwrap(), msg);
+        assert_eq!(receive(&samples, hard).unwrap(), msg);
+    }
+}
+
+#[test]
+fn a_weak_sample_no_longer_breaks_the_frame() {
+    let mut samples = frame("A B");
+    // the Lo closing the word break, read just above the threshold, makes a
+    // run of six Hi that no element has
+    let word_break = START_SEQUENCE.len()
+        + samples[START_SEQUENCE.len()..]
+            .windows(5)
+            .position(|w| w[..4].iter().all(|s| s.0 > 0) && w[4].0 < 0)
+            .unwrap()
+        + 4;
+    samples[word_break] = Soft(5);
+
+    assert!(receive(&samples, hard).is_err());
+    assert_eq!(receive(&samples, soft).unwrap(), "A B");
+}
+
+#[test]
+fn the_trellis_never_fails_on_noise() {
+    let mut decoder = UnaryCode::COMPACT.decoder();
+    let mut state = 7u32;
+    for _ in 0..10_000 {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        decoder.push_soft(Soft(state as i16)).unwrap();
+    }
+}
+
+#[test]
+fn an_impossible_run_doesnt_silence_the_next_frame() {
+    let mut decoder = MorseDecoder::new();
+    // no element is that many Hi long, and the Lo after it reads as more dots
+    // than any char has
+    let garbage = START_SEQUENCE
+        .iter()
+        .map(|bit| Soft::certain(*bit))
+        .chain([Soft(100); 20])
+        .chain([Soft(-100); 32]);
+    let failed = garbage
+        .filter_map(|sample| decoder.push_soft(sample).err())
+        .count();
+    assert!(failed > 0);
+
+    let mut text = String::new();
+    let mut ended = false;
+    for sample in frame("SOS") {
+        for event in decoder.push_soft(sample).unwrap() {
+            match event {
+                DecoderEvent::Symbol(MorseSymbol::Char(c)) => text.push(c),
+                DecoderEvent::FrameEnd => ended = true,
+                _ => {}
+            }
+        }
+    }
+    assert!(ended);
+    assert_eq!(text, "SOS");
+}
+
+#[test]
+fn the_line_decoder_keeps_going_after_an_impossible_run() {
+    let mut line = UnaryCode::COMPACT.decoder();
+    for _ in 0..20 {
+        line.push_soft(Soft(100)).unwrap();
+    }
+
+    // a dash and a char break, then enough idle to flush them out
+    let mut elements = Vec::new();
+    for sample in [-100, 100, 100, -100, 100, -100]
+        .into_iter()
+        .chain([-100; 16])
+    {
+        elements.extend(line.push_soft(Soft(sample)).unwrap());
+    }
+    let dash = elements
+        .windows(2)
+        .position(|w| w == [MorseBit::Dash, MorseBit::CharBreak]);
+    assert!(dash.is_some(), "{elements:?}");
+}
@@ -1,19 +1,62 @@
 use ::log::info;
 use esp_idf_svc::hal::units::Hertz;
-use log::error;
+use log::{error, warn};
+use morse::{ErrorKind, FrameDecoder, Framing, LinkConfig, MorseDecoder};
 
-use crate::parser::{ListeningForMessage, Parser, Processing};
+mod receiver;
 
-// const SAMPLE_HERTZ: u64 = 83255;
-const SAMPLE_HERTZ: u64 = 83322;
-// const SAMPLE_HERTZ: u64 = 83262;
-// const SAMPLE_HERTZ: u64 = 1110;
+use receiver::{Receiver, Reception};
 
 const SAMPLE_STEP: u64 = 100;
 
-const HIGH_THRESHOLD: u16 = 210;
+// largest frame the `bytes` link will hold on to, error correction included
+const MAX_FRAME: usize = 256;
+
+#[cfg(all(feature = "itu", feature = "manchester"))]
+compile_error!("`itu` decodes plain morse, it can't be manchester coded too");
+
+#[cfg(all(feature = "itu", feature = "bytes"))]
+compile_error!("`itu` decodes plain morse, it can't carry binary frames");
+
+#[cfg(all(feature = "itu", feature = "hardened"))]
+compile_error!("`itu` decodes plain morse, it can't take a hex trailer");
+
+// has to match the tx. sample rate and threshold come from the preset too,
+// change them here if this board reads the LED differently
+fn link_config() -> LinkConfig {
+    let mut link = if cfg!(feature = "itu") {
+        LinkConfig::ITU
+    } else if cfg!(feature = "manchester") {
+        LinkConfig::MANCHESTER
+    } else {
+        LinkConfig::COMPACT
+    };
+    if cfg!(feature = "hardened") {
+        link.preamble = LinkConfig::HARDENED.preamble;
+        link.checksum = LinkConfig::HARDENED.checksum;
+        link.fec = LinkConfig::HARDENED.fec;
+    }
+    if cfg!(feature = "bytes") {
+        link.framing = Framing::Frames;
+    }
+    link
+}
+
+fn receiver(link: &LinkConfig) -> Box<dyn Receiver> {
+    match link.framing {
+        Framing::Morse => Box::new(receiver::TextReceiver::new(MorseDecoder::with_config(link))),
+        Framing::Frames => Box::new(receiver::FrameReceiver::new(frame_decoder(link))),
+    }
+}
+
+fn frame_decoder(link: &LinkConfig) -> FrameDecoder<heapless::Vec<u8, MAX_FRAME>> {
+    let decoder = FrameDecoder::with_config(link);
+    // the pre-shared key, 64 hex digits, has to be the one tx was built with
+    #[cfg(feature = "aead")]
+    let decoder = decoder.with_key(morse::FrameKey::from_hex(env!("MORSE_KEY")));
+    decoder
+}
 
-mod parser;
 fn main() -> anyhow::Result<()> {
     use esp_idf_svc::hal::adc::{AdcContConfig, AdcContDriver, AdcMeasurement, Attenuated};
     use esp_idf_svc::hal::peripherals::Peripherals;
@@ -23,7 +66,9 @@ fn main() -> anyhow::Result<()> {
 
     let peripherals = Peripherals::take()?;
 
-    let config = AdcContConfig::default().sample_freq(Hertz::from(SAMPLE_HERTZ as u32));
+    let link = link_config();
+
+    let config = AdcContConfig::default().sample_freq(Hertz::from(link.sample_hertz as u32));
 
     let adc_1_channel_0 = Attenuated::db11(peripherals.pins.gpio2);
     let mut adc = AdcContDriver::new(peripherals.adc1, &config, adc_1_channel_0)?;
@@ -35,12 +80,13 @@ fn main() -> anyhow::Result<()> {
     //Default to just read 100 measurements per each read
     let mut samples = [AdcMeasurement::default(); SAMPLE_STEP as usize];
 
-    let mut start_listener = Parser::new();
-    let mut message_listener: Option<Parser<ListeningForMessage>> = None;
-    let mut message_parser: Option<Parser<Processing>> = None;
+    let mut receiver = receiver(&link);
+    let mut recover_bit = link.clock();
 
-    let mut perfect_reads = 0;
     let mut successful_reads = 0;
+    let mut rejected_reads = 0;
+    let mut forged_reads = 0;
+    let mut replayed_reads = 0;
     let mut attempts = 0;
 
     loop {
@@ -50,57 +96,59 @@ fn main() -> anyhow::Result<()> {
         };
 
         for measurement in &samples[0..num_read] {
-            if let Some(ref mut parser) = message_parser {
-                match parser.message() {
-                    Ok(msg) => {
-                        successful_reads += 1;
-                        let perf_msg = morse::MSG.to_lowercase();
-                        if msg == perf_msg {
-                            perfect_reads += 1;
-                        }
-
-                        // lets crunch some numbers here
-                        let read_rate: f32 = (successful_reads as f32 / attempts as f32) * 100.0;
-                        let perfect_rate: f32 = (perfect_reads as f32 / attempts as f32) * 100.0;
-
-                        info!("Message          : {msg}");
-                        info!("Read accuracy    : {read_rate}%");
-                        info!("Perfect accuracy : {perfect_rate}%");
-                        info!("Attempts         : {attempts}");
-                        println!("\n\n")
-                    }
-                    Err(e) => {
-                        error!("failed to parse message! {e:?}");
-                        // info!("morse_seq: {:#?}", parser.morse_seq);
-                        // info!("bit_seq: {:#?}", parser.bit_seq);
-                        // info!("light vals: {:#?}", parser.raw_val_buf);
-                    }
+            #[cfg(not(feature = "soft"))]
+            let bit = recover_bit.push(link.sample(measurement.data()));
+            // keep how far off the threshold each reading was, the decoder
+            // weighs near misses less than clear ones
+            #[cfg(feature = "soft")]
+            let bit = recover_bit.push_soft(link.soft_sample(measurement.data()));
+            let bit = match bit {
+                Ok(Some(bit)) => bit,
+                Ok(None) => continue,
+                Err(e) => {
+                    error!("lost the clock! {e}");
+                    receiver.reset();
+                    continue;
+                }
+            };
+
+            #[cfg(not(feature = "soft"))]
+            let received = receiver.push(bit);
+            #[cfg(feature = "soft")]
+            let received = receiver.push_soft(bit);
+            match received {
+                Ok(None) => {}
+                Ok(Some(Reception::Started)) => attempts += 1,
+                // the checksum already passed, so whatever was sent got here intact
+                Ok(Some(Reception::Finished)) => {
+                    let msg = receiver.message();
+                    successful_reads += 1;
+
+                    // lets crunch some numbers here
+                    let read_rate: f32 = (successful_reads as f32 / attempts as f32) * 100.0;
+                    let reject_rate: f32 = (rejected_reads as f32 / attempts as f32) * 100.0;
+
+                    info!("Message          : {msg}");
+                    info!("Read accuracy    : {read_rate}%");
+                    info!("Rejected frames  : {reject_rate}%");
+                    info!("Attempts         : {attempts}");
+                    println!("\n\n")
+                }
+                Err(e) if matches!(e.kind(), ErrorKind::ChecksumMismatch { .. }) => {
+                    rejected_reads += 1;
+                    error!("corrupted message! {e}");
+                }
+                // got through intact, but not from anyone with our key
+                Err(e) if e.kind() == ErrorKind::AuthenticationFailed => {
+                    forged_reads += 1;
+                    warn!("refused an unauthenticated frame ({forged_reads} so far)! {e}");
                 }
-                message_listener = None;
-                message_parser = None;
-                start_listener = Parser::default();
-            } else if let Some(ref mut listener) = message_listener {
-                match listener.process_light_val(measurement.data()) {
-                    Some(Ok(parser)) => {
-                        message_parser = Some(parser);
-                        message_listener = None;
-                    }
-                    Some(Err(e)) => {
-                        error!("Failed during measurement! {e:?}");
-                        // info!("morse_seq: {:#?}", listener.morse_seq);
-                        // info!("bit_seq: {:#?}", listener.bit_seq);
-                        // info!("light vals: {:#?}", listener.raw_val_buf);
-                        start_listener = Parser::default();
-                        message_parser = None;
-                        message_listener = None;
-                    }
-
-                    None => continue,
+                // sealed by us, but heard before
+                Err(e) if e.kind() == ErrorKind::ReplayedFrame => {
+                    replayed_reads += 1;
+                    warn!("refused a replayed frame ({replayed_reads} so far)! {e}");
                 }
-            } else if let Some(listener) = start_listener.process_light_val(measurement.data()) {
-                message_listener = Some(listener);
-                start_listener = Parser::default();
-                attempts += 1;
+                Err(e) => error!("failed to parse message! {e}"),
             }
         }
     }
@@ -0,0 +1,151 @@
+use core::fmt::Write;
+
+use log::{info, warn};
+#[cfg(feature = "soft")]
+use morse::Soft;
+use morse::{
+    Bit, Buffer, DecoderEvent, DecoderEvents, FrameDecoder, FrameEvent, LineCode, MessageType,
+    MorseDecoder, MorseError, MorseSymbol,
+};
+
+/// the frame boundaries the main loop keeps stats on
+pub enum Reception {
+    Started,
+    /// the message is complete, see [`Receiver::message`]
+    Finished,
+}
+
+/// hides whether the link carries morse text or raw bytes from the main loop
+pub trait Receiver {
+    fn push(&mut self, bit: Bit) -> Result<Option<Reception>, MorseError>;
+
+    /// feeds a reading that hasn't been thresholded yet, links that can't
+    /// use it get the hard decision
+    #[cfg(feature = "soft")]
+    fn push_soft(&mut self, soft: Soft) -> Result<Option<Reception>, MorseError> {
+        self.push(soft.hard())
+    }
+
+    fn reset(&mut self);
+
+    /// everything received in the current (or last finished) frame
+    fn message(&self) -> &str;
+}
+
+/// collects decoded symbols into a string, prosigns written as `<AR>`
+pub struct TextReceiver<C: LineCode> {
+    decoder: MorseDecoder<C>,
+    msg: String,
+}
+
+impl<C: LineCode> TextReceiver<C> {
+    pub fn new(decoder: MorseDecoder<C>) -> Self {
+        Self {
+            decoder,
+            msg: String::new(),
+        }
+    }
+
+    fn collect(&mut self, events: DecoderEvents) -> Option<Reception> {
+        let mut reception = None;
+
+        for event in events {
+            match event {
+                DecoderEvent::FrameStart => {
+                    self.msg.clear();
+                    reception = Some(Reception::Started);
+                }
+                DecoderEvent::Symbol(MorseSymbol::Char(c)) => self.msg.push(c),
+                DecoderEvent::Symbol(MorseSymbol::Prosign(p)) => {
+                    let _ = write!(self.msg, "<{p:?}>");
+                }
+                DecoderEvent::FrameEnd => reception = Some(Reception::Finished),
+            }
+        }
+
+        reception
+    }
+}
+
+impl<C: LineCode> Receiver for TextReceiver<C> {
+    fn push(&mut self, bit: Bit) -> Result<Option<Reception>, MorseError> {
+        let events = self.decoder.push(bit)?;
+        Ok(self.collect(events))
+    }
+
+    #[cfg(feature = "soft")]
+    fn push_soft(&mut self, soft: Soft) -> Result<Option<Reception>, MorseError> {
+        let events = self.decoder.push_soft(soft)?;
+        Ok(self.collect(events))
+    }
+
+    fn reset(&mut self) {
+        self.decoder.reset();
+    }
+
+    fn message(&self) -> &str {
+        &self.msg
+    }
+}
+
+/// binary frames, with text payloads shown as is and anything else as hex.
+/// gaps in the sequence numbers are logged as lost frames. with a key set the
+/// decoder already refused any frame that doesn't move the sequence forward
+pub struct FrameReceiver<B: Buffer<u8>> {
+    decoder: FrameDecoder<B>,
+    msg: String,
+    last_sequence: Option<u16>,
+}
+
+impl<B: Buffer<u8>> FrameReceiver<B> {
+    pub fn new(decoder: FrameDecoder<B>) -> Self {
+        Self {
+            decoder,
+            msg: String::new(),
+            last_sequence: None,
+        }
+    }
+}
+
+impl<B: Buffer<u8>> Receiver for FrameReceiver<B> {
+    fn push(&mut self, bit: Bit) -> Result<Option<Reception>, MorseError> {
+        let header = match self.decoder.push(bit)? {
+            Some(FrameEvent::FrameStart) => return Ok(Some(Reception::Started)),
+            Some(FrameEvent::FrameEnd(header)) => header,
+            None => return Ok(None),
+        };
+
+        if let Some(last) = self.last_sequence {
+            let lost = header.sequence.wrapping_sub(last).wrapping_sub(1);
+            // a jump back is tx starting over, not most of the sequence lost
+            if lost >= u16::MAX / 2 {
+                info!("tx started counting again at #{}", header.sequence);
+            } else if lost > 0 {
+                warn!("lost {lost} frame(s) before #{}", header.sequence);
+            }
+        }
+        self.last_sequence = Some(header.sequence);
+
+        let payload: Vec<u8> = self.decoder.message().collect();
+        self.msg.clear();
+        match (header.message_type, core::str::from_utf8(&payload)) {
+            (MessageType::Text, Ok(text)) => self.msg.push_str(text),
+            _ => {
+                let _ = write!(self.msg, "{:?} #{}:", header.message_type, header.sequence);
+                for byte in &payload {
+                    let _ = write!(self.msg, " {byte:02x}");
+                }
+            }
+        }
+
+        Ok(Some(Reception::Finished))
+    }
+
+    fn reset(&mut self) {
+        self.decoder.reset();
+    }
+
+    fn message(&self) -> &str {
+        &self.msg
+    }
+}
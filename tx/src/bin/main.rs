@@ -12,16 +12,61 @@ use esp_hal::delay::Delay;
 use esp_hal::gpio::{Input, InputConfig, Output, OutputConfig};
 use esp_hal::main;
 use esp_hal::time::{Duration, Instant};
-use morse::{
-    Bit, BitSequece, MSG, MorseBit, MorseConversion, MorseError, START_SEQUENCE, TIME_STEP_MICROS,
-};
+use morse::{AnyLineCode, Bit, Framing, LinkConfig, MorseError, Transmission};
 use {esp_backtrace as _, esp_println as _};
 
 extern crate alloc;
 
 esp_bootloader_esp_idf::esp_app_desc!();
 
-type DataPacket = heapless::Vec<Bit, 1000>;
+// const MESSAGE: &str = "Surendra";
+// const MESSAGE: &str = "suri.codes";
+
+// const MESSAGE: &str = "e";
+// const MESSAGE: &str = "eeeeeeeeeeeeeeeeeeeeeeeeeee";
+// const MESSAGE: &str = "eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee";
+
+// const MESSAGE: &str = "EEEEEEEEEEEEETTTTTTTTTTAAAAAAAAAOOOOOOOOOIIIIIIIINNNNNNNSSSSSSHHHHHHRRRRRRDDDDDDLLLLLCCCCUUUUMMMMWWWFFFGGGYYYPPPBBVVKKJJXQZ";
+// const MESSAGE: &str = "Hello ESP32";
+const MESSAGE: &str = "UCSC CSE 121 ABCDEFGHIJKLM NOPQRSTUVWXYZ 12345 67890";
+
+// room for the message once it's sealed, the tag included. only `aead` needs it
+const MAX_FRAME: usize = 256;
+
+#[cfg(all(feature = "itu", feature = "manchester"))]
+compile_error!("`itu` keys plain morse for humans, it can't be manchester coded too");
+
+#[cfg(all(feature = "itu", feature = "bytes"))]
+compile_error!("`itu` keys plain morse for humans, it can't carry binary frames");
+
+#[cfg(all(feature = "itu", feature = "hardened"))]
+compile_error!("`itu` keys plain morse for humans, it can't take a hex trailer");
+
+// the compact machine code by default, real morse that a human can copy with
+// the `itu` feature, a self clocking line with `manchester`. `hardened` adds
+// the preamble, trailer and error correction from `LinkConfig::HARDENED`.
+// `bytes` skips morse and sends the message in versioned binary frames, which
+// `compress` squeezes first. rx has to be set up the same way, except for
+// `compress`
+fn link_config() -> LinkConfig {
+    let mut link = if cfg!(feature = "itu") {
+        LinkConfig::ITU
+    } else if cfg!(feature = "manchester") {
+        LinkConfig::MANCHESTER
+    } else {
+        LinkConfig::COMPACT
+    };
+    if cfg!(feature = "hardened") {
+        link.preamble = LinkConfig::HARDENED.preamble;
+        link.checksum = LinkConfig::HARDENED.checksum;
+        link.fec = LinkConfig::HARDENED.fec;
+    }
+    if cfg!(feature = "bytes") {
+        link.framing = Framing::Frames;
+    }
+    link.compress = cfg!(feature = "compress");
+    link
+}
 
 #[main]
 fn main() -> ! {
@@ -37,46 +82,33 @@ fn main() -> ! {
 
     esp_alloc::heap_allocator!(#[esp_hal::ram(reclaimed)] size: 65536);
 
-    // lets form the data packet
-    let data_packet = form_data_packet()
-        .inspect_err(|_| {
-            error!("error forming data packet!");
-        })
-        .unwrap();
-
-    let delay = Delay::new();
-
-    let mut running_avg_recv_freq: f64 = 0.0;
-    let mut transmits = 0;
-
-    info!("Calibrating...");
-    for _ in 1..1000 {
-        for bit in &START_SEQUENCE {
-            hold_bit_for_time_step(&mut led, bit, &delay);
-        }
-
-        let char_start = Instant::now();
+    // the session in front of sealed frames has to be random, which takes
+    // the adc's noise until it's dropped
+    #[cfg(feature = "aead")]
+    let _entropy = esp_hal::rng::TrngSource::new(peripherals.RNG, peripherals.ADC1);
 
-        for bit in &data_packet {
-            hold_bit_for_time_step(&mut led, bit, &delay);
-        }
-
-        let elapsed_char_micros = char_start.elapsed().as_micros();
+    let link = link_config();
+    let message = MESSAGE;
+    info!("link: {}", link);
 
-        let char_bits = data_packet.len();
+    let mut numbering = Numbering::new();
 
-        let expected_time = char_bits as usize * TIME_STEP_MICROS as usize;
+    // make sure the whole message is encodable before we start streaming it
+    transmission(&link, message, &numbering, &mut [0; MAX_FRAME])
+        .inspect_err(|e| {
+            error!("error encoding message! {}", e);
+        })
+        .unwrap();
 
-        // should calculate what the ideal receiver freq should be
-        let optimal_receiver_freq = 1e6
-            / (((elapsed_char_micros - expected_time as u64) as f64 / char_bits as f64)
-                + TIME_STEP_MICROS as f64);
+    let delay = Delay::new();
 
-        transmits += 1;
-        running_avg_recv_freq = running_avg_recv_freq
-            + ((optimal_receiver_freq - running_avg_recv_freq) / transmits as f64);
+    // tuning the rx sample rate only matters for the plain compact code, at
+    // morse speeds the receiver resamples on every edge anyway, and
+    // manchester carries its own clock
+    if link.biphase.is_none() && matches!(link.line_code, AnyLineCode::Unary(_)) {
+        calibrate(&mut led, &delay, &link, message, &mut numbering);
     }
-    info!("optimal recv freq :  {} Hz", running_avg_recv_freq);
+
     info!("Press boot button to start transmitting message!");
 
     let mut running_avg_recv_freq: f64 = 0.0;
@@ -89,101 +121,161 @@ fn main() -> ! {
     }
 
     loop {
-        info!("sending start sequence!");
-        let bit_start = Instant::now();
-        for bit in &START_SEQUENCE {
-            hold_bit_for_time_step(&mut led, bit, &delay);
-        }
-
-        let char_start = Instant::now();
-
-        for bit in &data_packet {
-            hold_bit_for_time_step(&mut led, bit, &delay);
-        }
+        info!("sending message!");
+        let start = Instant::now();
 
-        let elapsed_char_micros = char_start.elapsed().as_micros();
-        let elapsed_bit_micros = bit_start.elapsed().as_micros();
+        let bits = transmit_message(&mut led, &delay, &link, message, &numbering);
+        numbering.next();
 
-        let bits = data_packet.len() + START_SEQUENCE.len();
-        let char_bits = data_packet.len();
+        let elapsed_micros = start.elapsed().as_micros();
 
-        let char_per_sec = (MSG.len() as f64 * 1.0e6) / elapsed_char_micros as f64;
-        let bits_per_sec = (bits as f64 * 1.0e6) / elapsed_bit_micros as f64;
-        let expected_time = char_bits as usize * TIME_STEP_MICROS as usize;
+        let char_per_sec = (message.len() as f64 * 1.0e6) / elapsed_micros as f64;
+        let bits_per_sec = (bits as f64 * 1.0e6) / elapsed_micros as f64;
+        let expected_time = bits as u64 * link.level_micros();
 
         // should calculate what the ideal receiver freq should be
         let optimal_receiver_freq = 1e6
-            / (((elapsed_char_micros - expected_time as u64) as f64 / char_bits as f64)
-                + TIME_STEP_MICROS as f64);
+            / (((elapsed_micros - expected_time) as f64 / bits as f64)
+                + link.level_micros() as f64);
 
         transmits += 1;
         running_avg_recv_freq = running_avg_recv_freq
             + ((optimal_receiver_freq - running_avg_recv_freq) / transmits as f64);
 
-        info!("Message           :  {}", MSG);
-        info!("transmission time :  {:#?} micros", elapsed_char_micros);
+        info!("Message           :  {}", message);
+        info!("transmission time :  {:#?} micros", elapsed_micros);
         info!("optimal recv freq :  {} Hz", running_avg_recv_freq);
         info!("chars per second  :  {:#?}", char_per_sec);
         info!("bits per second   :  {:#?}", bits_per_sec);
-        info!("total msg bits    :  {:#?}", char_bits);
         info!("total bits        :  {:#?}", bits);
         info!("\n\n");
 
-        // print_data_packet(&data_packet);
-
         // delay.delay(Duration::from_secs(2));
         delay.delay(Duration::from_millis(120));
     }
-    // }
-    // }
 }
 
-#[allow(unused)]
-fn print_data_packet(data_packet: &DataPacket) {
-    for bit in data_packet {
-        match bit {
-            Bit::Hi => info!("Hi"),
-            Bit::Lo => info!("Lo"),
-        }
+fn calibrate(
+    led: &mut Output<'_>,
+    delay: &Delay,
+    link: &LinkConfig,
+    message: &str,
+    numbering: &mut Numbering,
+) {
+    let mut running_avg_recv_freq: f64 = 0.0;
+    let mut transmits = 0;
+
+    info!("Calibrating...");
+    for _ in 1..1000 {
+        let start = Instant::now();
+
+        let bits = transmit_message(led, delay, link, message, numbering);
+        numbering.next();
+
+        let elapsed_micros = start.elapsed().as_micros();
+
+        let expected_time = bits as u64 * link.level_micros();
+
+        // should calculate what the ideal receiver freq should be
+        let optimal_receiver_freq = 1e6
+            / (((elapsed_micros - expected_time) as f64 / bits as f64)
+                + link.level_micros() as f64);
+
+        transmits += 1;
+        running_avg_recv_freq = running_avg_recv_freq
+            + ((optimal_receiver_freq - running_avg_recv_freq) / transmits as f64);
     }
+    info!("optimal recv freq :  {} Hz", running_avg_recv_freq);
 }
 
-fn form_data_packet() -> Result<DataPacket, MorseError> {
-    let mut packet = DataPacket::new();
+/// streams the preamble and `message` straight out of the encoder, returning
+/// how many levels were sent
+fn transmit_message(
+    led: &mut Output<'_>,
+    delay: &Delay,
+    link: &LinkConfig,
+    message: &str,
+    numbering: &Numbering,
+) -> usize {
+    let mut bits = 0;
+    let mut buffer = [0; MAX_FRAME];
+    // the message was checked up front, so there are no errors to skip here
+    for bit in transmission(link, message, numbering, &mut buffer).expect("message was checked") {
+        hold_bit_for_time_step(led, bit, delay, link.level_micros());
+        bits += 1;
+    }
+    bits
+}
 
-    let msg_len = MSG.len();
+// sealed with the pre-shared key under `aead`, the buffer goes unused otherwise
+#[cfg(feature = "aead")]
+fn transmission<'a>(
+    link: &LinkConfig,
+    message: &'a str,
+    numbering: &Numbering,
+    buffer: &'a mut [u8],
+) -> Result<Transmission<'a>, MorseError> {
+    // 64 hex digits, rx has to be built with the same one
+    const KEY: morse::FrameKey = morse::FrameKey::from_hex(env!("MORSE_KEY"));
+    link.sealed_transmission(message, numbering.sequence, numbering.session, &KEY, buffer)
+}
 
-    for (i, char) in MSG.to_lowercase().chars().enumerate() {
-        let m_seq = char
-            .to_morse_bit_sequence()
-            .expect("should be a valid bit sequence");
-        for m_bit in m_seq {
-            let b_seq: BitSequece = m_bit.into();
-            for bit in b_seq {
-                packet.push(bit).map_err(|_| MorseError::FullBuffer)?;
-            }
-        }
+#[cfg(not(feature = "aead"))]
+fn transmission<'a>(
+    link: &LinkConfig,
+    message: &'a str,
+    numbering: &Numbering,
+    _buffer: &'a mut [u8],
+) -> Result<Transmission<'a>, MorseError> {
+    link.transmission(message, numbering.sequence)
+}
+
+/// every frame gets the next sequence number, so rx can tell them apart and
+/// notice the ones it missed. sealed frames also carry a session, drawn again
+/// on every boot and whenever the sequence wraps, so the key never sees the
+/// same nonce twice
+struct Numbering {
+    sequence: u16,
+    session: u64,
+}
 
-        let delimeter: BitSequece = if i + 1 == msg_len {
-            MorseBit::LineBreak
-        } else {
-            MorseBit::CharBreak
+impl Numbering {
+    fn new() -> Self {
+        Self {
+            sequence: 0,
+            session: new_session(),
         }
-        .into();
+    }
 
-        for bit in delimeter {
-            packet.push(bit).map_err(|_| MorseError::FullBuffer)?;
+    fn next(&mut self) {
+        self.sequence = self.sequence.wrapping_add(1);
+        if self.sequence == 0 {
+            self.session = new_session();
         }
     }
+}
+
+// read off the entropy source `main` keeps running
+#[cfg(feature = "aead")]
+fn new_session() -> u64 {
+    let mut session = [0; 8];
+    esp_hal::rng::Trng::try_new()
+        .expect("the entropy source runs for as long as main")
+        .read(&mut session);
+    u64::from_le_bytes(session)
+}
 
-    Ok(packet)
+// plain frames only need the sequence
+#[cfg(not(feature = "aead"))]
+fn new_session() -> u64 {
+    0
 }
 
 #[inline(always)]
-fn hold_bit_for_time_step(led: &mut Output<'_>, bit: &morse::Bit, delay: &Delay) {
+fn hold_bit_for_time_step(led: &mut Output<'_>, bit: Bit, delay: &Delay, micros: u64) {
     match bit {
-        morse::Bit::Hi => led.set_high(),
-        morse::Bit::Lo => led.set_low(),
+        Bit::Hi => led.set_high(),
+        Bit::Lo => led.set_low(),
     }
-    delay.delay_micros(morse::TIME_STEP_MICROS as u32);
+    delay.delay_micros(micros as u32);
 }